@@ -1,13 +1,17 @@
 use crate::config::Config;
+use crate::git::Commit;
 use regex::Regex;
+use serde::Serialize;
 
 const BUMP_COMMIT_PREFIX: &str = "chore: bump version";
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BumpType {
     Major,
     Minor,
     Patch,
+    #[default]
     None,
 }
 
@@ -40,42 +44,123 @@ impl BumpType {
     }
 }
 
+/// What marked a commit as breaking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakingSource {
+    /// `!` after the type or scope, e.g. `feat!:`
+    Bang,
+    /// "breaking change" mentioned in the subject
+    Subject,
+    /// A `BREAKING CHANGE:` or `BREAKING-CHANGE:` footer. Reported, but it
+    /// doesn't force a major bump, as changelogs only see subjects.
+    Footer,
+}
+
+impl BreakingSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BreakingSource::Bang => "bang",
+            BreakingSource::Subject => "subject",
+            BreakingSource::Footer => "footer",
+        }
+    }
+
+    /// Whether this source makes the commit a major bump
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, BreakingSource::Footer)
+    }
+}
+
+/// How a single commit was classified
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitAnalysis {
+    pub hash: String,
+    /// First line of the commit message
+    pub message: String,
+    /// Conventional commit type, if the message follows the format
+    #[serde(rename = "type")]
+    pub commit_type: Option<String>,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub breaking_source: Option<BreakingSource>,
+    /// The `types` entry that decided the bump, e.g. `feat = minor`
+    pub rule: Option<String>,
+    pub bump: BumpType,
+    /// Whether the type is configured in `types` (or the commit is breaking)
+    pub known: bool,
+    /// Release commits made by grubble itself are ignored
+    pub skipped: bool,
+}
+
 pub struct AnalysisResult {
     pub bump: BumpType,
+    /// Every commit in the range, in order, including skipped ones
+    pub commits: Vec<CommitAnalysis>,
     pub triggering_commits: Vec<String>,
     pub unknown_commits: Vec<String>,
 }
 
-pub fn analyse_commits(commits: &[String], config: &Config) -> AnalysisResult {
-    let substantive_commits: Vec<_> = commits
-        .iter()
-        .filter(|msg| !msg.starts_with(BUMP_COMMIT_PREFIX))
-        .collect();
-
-    if substantive_commits.is_empty() {
-        return AnalysisResult {
-            bump: BumpType::None,
-            triggering_commits: vec![],
-            unknown_commits: vec![],
-        };
+fn breaking_source(subject: &str, body: &str, has_bang: bool) -> Option<BreakingSource> {
+    if has_bang {
+        Some(BreakingSource::Bang)
+    } else if subject.to_lowercase().contains("breaking change") {
+        Some(BreakingSource::Subject)
+    } else if body
+        .lines()
+        .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"))
+    {
+        Some(BreakingSource::Footer)
+    } else {
+        None
     }
+}
 
+pub fn analyse_commits(commits: &[Commit], config: &Config) -> AnalysisResult {
     let mut bump = BumpType::None;
+    let mut analysed = Vec::new();
     let mut triggering_commits = Vec::new();
     let mut unknown_commits = Vec::new();
 
-    let commit_type_regex = Regex::new(r"^([a-z]+)(?:\([^)]+\))?(!?):").unwrap();
+    let commit_type_regex = Regex::new(r"^([a-z]+)(?:\(([^)]+)\))?(!?):").unwrap();
+
+    for commit in commits {
+        let msg = commit.subject();
+        let mut analysis = CommitAnalysis {
+            hash: commit.hash.clone(),
+            message: msg.to_string(),
+            commit_type: None,
+            scope: None,
+            breaking: false,
+            breaking_source: None,
+            rule: None,
+            bump: BumpType::None,
+            known: false,
+            skipped: msg.starts_with(BUMP_COMMIT_PREFIX),
+        };
+
+        if analysis.skipped {
+            analysed.push(analysis);
+            continue;
+        }
 
-    for msg in substantive_commits {
         if let Some(captures) = commit_type_regex.captures(msg) {
             let commit_type = captures.get(1).map(|m| m.as_str()).unwrap_or("");
-            let has_exclamation = captures.get(2).map(|m| m.as_str()).unwrap_or("") == "!";
+            let has_exclamation = captures.get(3).map(|m| m.as_str()).unwrap_or("") == "!";
 
-            let has_breaking = has_exclamation || msg.to_lowercase().contains("breaking change");
+            analysis.commit_type = Some(commit_type.to_string());
+            analysis.scope = captures.get(2).map(|m| m.as_str().to_string());
+            analysis.breaking_source = breaking_source(msg, commit.body(), has_exclamation);
+            analysis.breaking = analysis
+                .breaking_source
+                .is_some_and(|source| source.is_breaking());
+            analysis.known = true;
 
-            let commit_bump = if has_breaking {
+            let commit_bump = if analysis.breaking {
                 BumpType::Major
             } else if let Some(bump_str) = config.types.get(commit_type) {
+                analysis.rule = Some(format!("{} = {}", commit_type, bump_str));
                 match bump_str.as_str() {
                     "major" => BumpType::Major,
                     "minor" => BumpType::Minor,
@@ -83,6 +168,7 @@ pub fn analyse_commits(commits: &[String], config: &Config) -> AnalysisResult {
                     _ => BumpType::None,
                 }
             } else {
+                analysis.known = false;
                 unknown_commits.push(msg.to_string());
                 BumpType::None
             };
@@ -92,11 +178,15 @@ pub fn analyse_commits(commits: &[String], config: &Config) -> AnalysisResult {
             if commit_bump != BumpType::None {
                 triggering_commits.push(format!("{}: {}", commit_bump.label(), msg));
             }
+            analysis.bump = commit_bump;
         }
+
+        analysed.push(analysis);
     }
 
     AnalysisResult {
         bump,
+        commits: analysed,
         triggering_commits,
         unknown_commits,
     }
@@ -115,7 +205,7 @@ mod tests {
 
     #[test]
     fn test_analyse_commits_feat() {
-        let commits = vec!["feat: add new feature".to_string()];
+        let commits = vec![Commit::from("feat: add new feature")];
         let config = Config::default();
         let result = analyse_commits(&commits, &config);
 
@@ -124,7 +214,7 @@ mod tests {
 
     #[test]
     fn test_analyse_commits_fix() {
-        let commits = vec!["fix: resolve bug".to_string()];
+        let commits = vec![Commit::from("fix: resolve bug")];
         let config = Config::default();
         let result = analyse_commits(&commits, &config);
 
@@ -133,10 +223,57 @@ mod tests {
 
     #[test]
     fn test_analyse_commits_breaking() {
-        let commits = vec!["feat!: breaking change".to_string()];
+        let commits = vec![Commit::from("feat!: breaking change")];
+        let config = Config::default();
+        let result = analyse_commits(&commits, &config);
+
+        assert_eq!(result.bump, BumpType::Major);
+    }
+
+    #[test]
+    fn test_analyse_commits_records_classification() {
+        let commits = vec![
+            Commit::from("fix(api): resolve bug"),
+            Commit::from("tidy up"),
+        ];
+        let config = Config::default();
+        let result = analyse_commits(&commits, &config);
+
+        assert_eq!(result.commits.len(), 2);
+        assert_eq!(result.commits[0].commit_type.as_deref(), Some("fix"));
+        assert_eq!(result.commits[0].bump, BumpType::Patch);
+        assert!(result.commits[0].known);
+        assert_eq!(result.commits[1].commit_type, None);
+        assert!(!result.commits[1].known);
+    }
+
+    #[test]
+    fn test_analyse_commits_explains_breaking_source_and_rule() {
+        let commits = vec![
+            Commit::from("feat(api)!: new endpoint\n\nBREAKING CHANGE: removes v1 routes"),
+            Commit::from("fix: patch it\n\nThere is no breaking change here."),
+            Commit::from("docs: explain it\n\nBREAKING CHANGE: only in the body"),
+            Commit::from("chore: bump version to 1.0.0"),
+        ];
         let config = Config::default();
         let result = analyse_commits(&commits, &config);
 
         assert_eq!(result.bump, BumpType::Major);
+        assert_eq!(result.commits[0].scope.as_deref(), Some("api"));
+        assert_eq!(
+            result.commits[0].breaking_source,
+            Some(BreakingSource::Bang)
+        );
+        assert_eq!(result.commits[0].message, "feat(api)!: new endpoint");
+        assert_eq!(result.commits[1].breaking_source, None);
+        assert_eq!(result.commits[1].rule.as_deref(), Some("fix = patch"));
+        // Footers are reported but left alone, as changelogs only see subjects
+        assert_eq!(
+            result.commits[2].breaking_source,
+            Some(BreakingSource::Footer)
+        );
+        assert!(!result.commits[2].breaking);
+        assert_eq!(result.commits[2].bump, BumpType::None);
+        assert!(result.commits[3].skipped);
     }
 }
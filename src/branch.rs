@@ -0,0 +1,189 @@
+use crate::config::{BranchesConfig, OtherBranches};
+use crate::error::{BumperError, BumperResult};
+use crate::versioner::{Version, VersionRange};
+use regex::Regex;
+
+/// What the current branch may release, from the first matching rule in
+/// `branches.rules`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BranchPolicy {
+    pub branch: Option<String>,
+    /// Prerelease channel, e.g. `next` for `2.0.0-next.1`
+    pub prerelease: Option<String>,
+    /// Versions the branch may release
+    pub range: Option<VersionRange>,
+    /// Set when there are rules but none matches the branch
+    pub unmatched: Option<OtherBranches>,
+}
+
+impl BranchPolicy {
+    pub fn resolve(config: &BranchesConfig, branch: Option<&str>) -> Self {
+        let mut policy = BranchPolicy {
+            branch: branch.map(str::to_string),
+            ..BranchPolicy::default()
+        };
+        if config.rules.is_empty() {
+            return policy;
+        }
+
+        let rule = branch.and_then(|branch| {
+            config
+                .rules
+                .iter()
+                .find(|rule| matches_pattern(&rule.name, branch))
+        });
+        match rule {
+            Some(rule) => {
+                policy.prerelease = rule.prerelease.clone();
+                policy.range = rule.range.as_deref().and_then(|range| range.parse().ok());
+            }
+            None => policy.unmatched = Some(config.other),
+        }
+        policy
+    }
+
+    pub fn branch_name(&self) -> &str {
+        self.branch.as_deref().unwrap_or("HEAD")
+    }
+
+    /// Fail unless the branch may release for real
+    pub fn check_release(&self) -> BumperResult<()> {
+        match self.unmatched {
+            Some(OtherBranches::Refuse) => Err(BumperError::BranchNotReleasable(
+                self.branch_name().to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The version this branch releases for `candidate`, the next release
+    /// worked out from commits or `--release-as`. Prerelease branches number
+    /// their releases after the highest existing one in `existing`.
+    pub fn version(&self, candidate: Version, existing: &[Version]) -> BumperResult<Version> {
+        if let Some(range) = &self.range {
+            if !range.contains(&candidate) {
+                return Err(BumperError::VersionOutOfRange(
+                    candidate.to_string(),
+                    self.branch_name().to_string(),
+                    range.to_string(),
+                ));
+            }
+        }
+
+        match &self.prerelease {
+            Some(channel) if !candidate.is_prerelease() => {
+                let number = existing
+                    .iter()
+                    .filter(|version| version.release() == candidate)
+                    .filter_map(|version| version.prerelease_number(channel))
+                    .max()
+                    .map_or(1, |highest| highest + 1);
+                Ok(candidate.with_prerelease(channel, number))
+            }
+            _ => Ok(candidate),
+        }
+    }
+}
+
+/// Match a branch name against a pattern where `*` matches any characters
+fn matches_pattern(pattern: &str, branch: &str) -> bool {
+    let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+    Regex::new(&format!("^{}$", parts.join(".*")))
+        .map(|regex| regex.is_match(branch))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BranchRule;
+
+    fn config() -> BranchesConfig {
+        let rule = |name: &str, prerelease: Option<&str>, range: Option<&str>| BranchRule {
+            name: name.to_string(),
+            prerelease: prerelease.map(str::to_string),
+            range: range.map(str::to_string),
+        };
+        BranchesConfig {
+            rules: vec![
+                rule("main", None, None),
+                rule("next", Some("next"), None),
+                rule("release/1.x", None, Some("1.x")),
+                rule("hotfix/*", None, None),
+            ],
+            other: OtherBranches::Refuse,
+        }
+    }
+
+    fn version(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_matches_rules_in_order() {
+        let config = config();
+
+        assert_eq!(BranchPolicy::resolve(&config, Some("main")).unmatched, None);
+        assert_eq!(
+            BranchPolicy::resolve(&config, Some("next"))
+                .prerelease
+                .as_deref(),
+            Some("next")
+        );
+        assert!(BranchPolicy::resolve(&config, Some("release/1.x"))
+            .range
+            .is_some());
+        assert_eq!(
+            BranchPolicy::resolve(&config, Some("hotfix/crash")).unmatched,
+            None
+        );
+        assert!(matches!(
+            BranchPolicy::resolve(&config, Some("feature/x")).check_release(),
+            Err(BumperError::BranchNotReleasable(_))
+        ));
+        assert!(BranchPolicy::resolve(&config, None)
+            .check_release()
+            .is_err());
+        assert!(
+            BranchPolicy::resolve(&BranchesConfig::default(), Some("feature/x"))
+                .check_release()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_prerelease_numbers_follow_existing_tags() {
+        let policy = BranchPolicy::resolve(&config(), Some("next"));
+        let existing = [
+            version("2.0.0-next.1"),
+            version("2.0.0-next.2"),
+            version("1.0.0"),
+        ];
+
+        assert_eq!(
+            policy
+                .version(version("2.0.0"), &existing)
+                .unwrap()
+                .to_string(),
+            "2.0.0-next.3"
+        );
+        assert_eq!(
+            policy
+                .version(version("2.1.0"), &existing)
+                .unwrap()
+                .to_string(),
+            "2.1.0-next.1"
+        );
+    }
+
+    #[test]
+    fn test_range_rejects_versions_outside_it() {
+        let policy = BranchPolicy::resolve(&config(), Some("release/1.x"));
+
+        assert!(policy.version(version("1.5.0"), &[]).is_ok());
+        assert!(matches!(
+            policy.version(version("2.0.0"), &[]),
+            Err(BumperError::VersionOutOfRange(_, _, _))
+        ));
+    }
+}
@@ -1,30 +1,40 @@
 use crate::analyser::BumpType;
-use crate::error::BumperResult;
+use crate::config::{Config, DateSource, ExistingEntry};
+use crate::error::{BumperError, BumperResult};
+use crate::git;
+use crate::plan::FileUpdate;
 use crate::versioner::Version;
-use chrono::Local;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-const CHANGELOG_FILE: &str = "CHANGELOG.md";
+mod asciidoc;
+mod debian;
+mod json;
+mod markdown;
+mod rst;
 
 /// Represents a parsed changelog entry
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize)]
 struct ChangelogEntry {
     version: String,
     date: String,
     changes: Vec<Change>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct Change {
     category: ChangeCategory,
     description: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    breaking: bool,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum ChangeCategory {
     Added,
     Changed,
@@ -34,15 +44,60 @@ enum ChangeCategory {
     Security,
 }
 
+/// A release section of an existing changelog and its byte range in the file
+#[derive(Debug)]
+struct Section {
+    entry: ChangelogEntry,
+    start: usize,
+    end: usize,
+}
+
+/// Output format of a changelog file, selected from its path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Json,
+    AsciiDoc,
+    Rst,
+    Debian,
+}
+
+/// A changelog file to write, optionally limited to commits touching a directory
+#[derive(Debug, PartialEq, Eq)]
+pub struct ChangelogTarget {
+    pub path: PathBuf,
+    pub scope: Option<String>,
+}
+
+/// What happened to the changelog file for a given release
+#[derive(Debug, PartialEq, Eq)]
+pub enum EntryUpdate {
+    Inserted,
+    Replaced,
+    Skipped,
+}
+
 impl ChangeCategory {
-    fn as_str(&self) -> &'static str {
+    fn name(&self) -> &'static str {
         match self {
-            ChangeCategory::Added => "### Added",
-            ChangeCategory::Changed => "### Changed",
-            ChangeCategory::Deprecated => "### Deprecated",
-            ChangeCategory::Removed => "### Removed",
-            ChangeCategory::Fixed => "### Fixed",
-            ChangeCategory::Security => "### Security",
+            ChangeCategory::Added => "Added",
+            ChangeCategory::Changed => "Changed",
+            ChangeCategory::Deprecated => "Deprecated",
+            ChangeCategory::Removed => "Removed",
+            ChangeCategory::Fixed => "Fixed",
+            ChangeCategory::Security => "Security",
+        }
+    }
+
+    fn from_heading(heading: &str) -> Option<Self> {
+        match heading.trim() {
+            "Added" => Some(ChangeCategory::Added),
+            "Changed" => Some(ChangeCategory::Changed),
+            "Deprecated" => Some(ChangeCategory::Deprecated),
+            "Removed" => Some(ChangeCategory::Removed),
+            "Fixed" => Some(ChangeCategory::Fixed),
+            "Security" => Some(ChangeCategory::Security),
+            _ => None,
         }
     }
 
@@ -59,24 +114,462 @@ impl ChangeCategory {
     }
 }
 
-/// Categorize commits and generate changelog entry
-pub fn generate_changelog_entry(
+impl ChangelogEntry {
+    /// Iterate over changes grouped by category, in category order
+    fn grouped(&self) -> Vec<(ChangeCategory, Vec<&Change>)> {
+        let mut groups: Vec<(ChangeCategory, Vec<&Change>)> = Vec::new();
+        for change in &self.changes {
+            match groups.last_mut() {
+                Some((category, changes)) if *category == change.category => changes.push(change),
+                _ => groups.push((change.category, vec![change])),
+            }
+        }
+        groups
+    }
+}
+
+impl Format {
+    /// Pick the format from the file name: `.json`, `.adoc`/`.asciidoc`,
+    /// `.rst`, a file named `changelog` (as in `debian/changelog`), and
+    /// Markdown for everything else.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "json" => Format::Json,
+            "adoc" | "asciidoc" => Format::AsciiDoc,
+            "rst" => Format::Rst,
+            "" if path.file_name().is_some_and(|n| n == "changelog") => Format::Debian,
+            _ => Format::Markdown,
+        }
+    }
+
+    /// Content of a freshly created changelog, before any entries
+    fn header(&self) -> &'static str {
+        match self {
+            Format::Markdown => markdown::HEADER,
+            Format::AsciiDoc => asciidoc::HEADER,
+            Format::Rst => rst::HEADER,
+            Format::Json | Format::Debian => "",
+        }
+    }
+
+    fn parse(&self, content: &str) -> Vec<Section> {
+        match self {
+            Format::Markdown => markdown::parse(content),
+            Format::AsciiDoc => asciidoc::parse(content),
+            Format::Rst => rst::parse(content),
+            Format::Debian => debian::parse(content),
+            Format::Json => vec![],
+        }
+    }
+}
+
+/// Resolve the changelog files to write for a release.
+///
+/// Every path in `changelogFiles` is written for the whole repository. With
+/// `changelogPerPackage`, each package file in a subdirectory instead gets its
+/// own changelog(s) next to it, covering only commits that touch that directory.
+pub fn changelog_targets(config: &Config) -> Vec<ChangelogTarget> {
+    let mut targets: Vec<ChangelogTarget> = Vec::new();
+    let mut push = |target: ChangelogTarget| {
+        if !targets.iter().any(|t| t.path == target.path) {
+            targets.push(target);
+        }
+    };
+
+    let package_dirs: Vec<PathBuf> = if config.changelog_per_package {
+        config
+            .package_files
+            .iter()
+            .map(|file| {
+                Path::new(file)
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default()
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    if package_dirs.is_empty() || package_dirs.iter().any(|d| d.as_os_str().is_empty()) {
+        for file in &config.changelog_files {
+            push(ChangelogTarget {
+                path: PathBuf::from(file),
+                scope: None,
+            });
+        }
+    }
+
+    for dir in package_dirs.iter().filter(|d| !d.as_os_str().is_empty()) {
+        for file in &config.changelog_files {
+            push(ChangelogTarget {
+                path: dir.join(file),
+                scope: Some(dir.to_string_lossy().to_string()),
+            });
+        }
+    }
+
+    targets
+}
+
+/// Render the entry a release would add, without writing any file. Used to
+/// preview release notes, e.g. in a pull request comment.
+pub fn render_preview(
     version: &Version,
     commits: &[String],
-    _bump_type: BumpType,
-) -> BumperResult<()> {
-    generate_changelog_entry_at_path(version, commits, Path::new(CHANGELOG_FILE))
+    format: Format,
+    released: &DateTime<FixedOffset>,
+    config: &Config,
+) -> BumperResult<String> {
+    let entry = build_entry(version, &format_date(released, config)?, commits);
+
+    match format {
+        Format::Json => json::render_entry(&entry),
+        Format::Debian => Ok(debian::render(
+            &entry,
+            released,
+            &debian::Settings::resolve(config, "")?,
+        )),
+        _ => Ok(render_entry(&entry, format)),
+    }
+}
+
+/// Placeholders available in the `tagMessage` template
+pub const TAG_PLACEHOLDERS: [&str; 6] = ["version", "tag", "date", "bump", "summary", "notes"];
+
+/// The annotation of a release tag, rendered from the `tagMessage` template.
+/// `{notes}` is only filled in with `releaseNotes`.
+pub fn render_tag_message(
+    version: &Version,
+    bump: BumpType,
+    commits: &[String],
+    released: &DateTime<FixedOffset>,
+    config: &Config,
+) -> BumperResult<String> {
+    let entry = build_entry(version, &format_date(released, config)?, commits);
+    let notes = if config.release_notes {
+        render_plain_notes(&entry)
+    } else {
+        String::new()
+    };
+    let values = [
+        ("version", version.to_string()),
+        ("tag", format!("{}{}", config.tag_prefix, version)),
+        ("date", entry.date.clone()),
+        ("bump", bump.as_str().to_string()),
+        ("summary", summarize(&entry)),
+        ("notes", notes),
+    ];
+
+    let mut message = config.tag_message.clone();
+    for (name, value) in values {
+        message = message.replace(&format!("{{{}}}", name), &value);
+    }
+
+    // Empty placeholders would leave runs of blank lines behind
+    let mut lines: Vec<&str> = Vec::new();
+    for line in message.lines().map(str::trim_end) {
+        if !(line.is_empty() && lines.last().is_none_or(|last| last.is_empty())) {
+            lines.push(line);
+        }
+    }
+    Ok(lines.join("\n").trim_end().to_string())
+}
+
+/// Change counts, e.g. `3 changes (2 added, 1 fixed, 1 breaking)`
+fn summarize(entry: &ChangelogEntry) -> String {
+    let total = entry.changes.len();
+    if total == 0 {
+        return "no changes".to_string();
+    }
+
+    let mut counts: Vec<String> = entry
+        .grouped()
+        .iter()
+        .map(|(category, changes)| format!("{} {}", changes.len(), category.name().to_lowercase()))
+        .collect();
+    let breaking = entry.changes.iter().filter(|c| c.breaking).count();
+    if breaking > 0 {
+        counts.push(format!("{} breaking", breaking));
+    }
+
+    let noun = if total == 1 { "change" } else { "changes" };
+    format!("{} {} ({})", total, noun, counts.join(", "))
+}
+
+/// Sections as plain text; git would strip Markdown headings as comments
+fn render_plain_notes(entry: &ChangelogEntry) -> String {
+    entry
+        .grouped()
+        .iter()
+        .map(|(category, changes)| {
+            let items: Vec<String> = changes
+                .iter()
+                .map(|change| {
+                    let prefix = if change.breaking { "BREAKING: " } else { "" };
+                    format!("- {}{}", prefix, change.description)
+                })
+                .collect();
+            format!("{}:\n{}", category.name(), items.join("\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
 }
 
-/// Internal function that accepts a custom path for testing
-fn generate_changelog_entry_at_path(
+/// Compute the changelog with the entry for `version` added or replaced,
+/// without writing it. There is no file update when the existing entry is kept.
+pub fn plan_changelog_entry(
     version: &Version,
     commits: &[String],
     changelog_path: &Path,
-) -> BumperResult<()> {
-    let date = Local::now().format("%Y-%m-%d").to_string();
+    released: &DateTime<FixedOffset>,
+    config: &Config,
+) -> BumperResult<(EntryUpdate, Option<FileUpdate>)> {
+    let entry = build_entry(version, &format_date(released, config)?, commits);
+    let format = Format::from_path(changelog_path);
 
-    // Parse commits into categorized changes
+    // Read existing changelog or create header
+    let existing = if changelog_path.exists() {
+        Some(fs::read_to_string(changelog_path)?)
+    } else {
+        None
+    };
+
+    let (content, update) = if format == Format::Json {
+        let mut entries = match &existing {
+            Some(content) => json::parse(content)?,
+            None => Vec::new(),
+        };
+        let update = match entries.iter().position(|e| e.version == entry.version) {
+            Some(index) => {
+                if !replace_existing(config.changelog_existing, &entry.version, changelog_path)? {
+                    return Ok((EntryUpdate::Skipped, None));
+                }
+                entries[index] = entry;
+                EntryUpdate::Replaced
+            }
+            None => {
+                entries.insert(0, entry);
+                EntryUpdate::Inserted
+            }
+        };
+        (json::render_document(entries)?, update)
+    } else {
+        let mut content = existing
+            .clone()
+            .unwrap_or_else(|| format.header().to_string());
+        let sections = format.parse(&content);
+        let mut rendered = match format {
+            Format::Debian => debian::render(
+                &entry,
+                released,
+                &debian::Settings::resolve(config, &content)?,
+            ),
+            _ => render_entry(&entry, format),
+        };
+
+        let update = if let Some(index) = sections
+            .iter()
+            .position(|s| s.entry.version == entry.version)
+        {
+            if !replace_existing(config.changelog_existing, &entry.version, changelog_path)? {
+                return Ok((EntryUpdate::Skipped, None));
+            }
+            let existing = &sections[index];
+            // Keep the blank line separating this entry from the next one
+            if index + 1 < sections.len() {
+                rendered.push('\n');
+            }
+            content.replace_range(existing.start..existing.end, &rendered);
+            EntryUpdate::Replaced
+        } else {
+            // Insert after the header, before existing entries
+            let insertion_point = if let Some(first) = sections.first() {
+                // Add blank line after the new entry if there are existing entries
+                rendered.push('\n');
+                first.start
+            } else {
+                content.len()
+            };
+            content.insert_str(insertion_point, &rendered);
+            EntryUpdate::Inserted
+        };
+        (content, update)
+    };
+
+    Ok((
+        update,
+        Some(FileUpdate {
+            path: changelog_path.to_string_lossy().to_string(),
+            original: existing,
+            updated: content,
+        }),
+    ))
+}
+
+/// Work out when the release happened, according to the `releaseDate`
+/// settings. `source_date_epoch` is the value of `SOURCE_DATE_EPOCH`, if set.
+/// With the commit source this is HEAD's committer date.
+pub fn release_timestamp(
+    config: &Config,
+    source_date_epoch: Option<&str>,
+) -> BumperResult<DateTime<FixedOffset>> {
+    let seconds = match config.release_date.source {
+        DateSource::Now => Utc::now().timestamp(),
+        DateSource::Commit => git::get_commit_timestamp("HEAD")?,
+        DateSource::SourceDateEpoch => {
+            let value = source_date_epoch.ok_or_else(|| {
+                BumperError::ConfigError(
+                    "releaseDate.source is sourceDateEpoch but SOURCE_DATE_EPOCH is not set"
+                        .to_string(),
+                )
+            })?;
+            value.trim().parse::<i64>().map_err(|_| {
+                BumperError::ConfigError(format!("Invalid SOURCE_DATE_EPOCH: {}", value))
+            })?
+        }
+    };
+
+    let utc = Utc.timestamp_opt(seconds, 0).single().ok_or_else(|| {
+        BumperError::ConfigError(format!("Release timestamp out of range: {}", seconds))
+    })?;
+
+    let in_ci = std::env::var_os("CI").is_some();
+    Ok(
+        match parse_timezone(config.release_date.timezone.as_deref(), in_ci)? {
+            Some(offset) => utc.with_timezone(&offset),
+            None => utc.with_timezone(&Local).fixed_offset(),
+        },
+    )
+}
+
+/// Parse a `releaseDate.timezone` value. `None` means the local timezone.
+fn parse_timezone(value: Option<&str>, in_ci: bool) -> BumperResult<Option<FixedOffset>> {
+    let utc = FixedOffset::east_opt(0);
+    let value = match value {
+        Some(value) => value.trim(),
+        None if in_ci => return Ok(utc),
+        None => return Ok(None),
+    };
+
+    match value.to_lowercase().as_str() {
+        "utc" | "z" => Ok(utc),
+        "local" => Ok(None),
+        _ => DateTime::parse_from_rfc3339(&format!("2000-01-01T00:00:00{}", value))
+            .map(|parsed| Some(*parsed.offset()))
+            .map_err(|_| {
+                BumperError::ConfigError(format!(
+                    "Invalid releaseDate.timezone '{}': expected \"utc\", \"local\" or an offset like \"+02:00\"",
+                    value
+                ))
+            }),
+    }
+}
+
+/// Format the release date shown in entries with `releaseDate.format`
+fn format_date(released: &DateTime<FixedOffset>, config: &Config) -> BumperResult<String> {
+    let format = &config.release_date.format;
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(BumperError::ConfigError(format!(
+            "Invalid releaseDate.format: {}",
+            format
+        )));
+    }
+    Ok(released.format(format).to_string())
+}
+
+/// Apply the `changelogExisting` policy to an entry that is already present.
+/// Returns whether the entry should be replaced (`false` means skip it).
+fn replace_existing(policy: ExistingEntry, version: &str, path: &Path) -> BumperResult<bool> {
+    match policy {
+        ExistingEntry::Replace => Ok(true),
+        ExistingEntry::Skip => Ok(false),
+        ExistingEntry::Error => Err(BumperError::ChangelogEntryExists(
+            version.to_string(),
+            path.display().to_string(),
+        )),
+    }
+}
+
+/// Render a single entry in a text format (everything but JSON and Debian,
+/// which need extra context)
+fn render_entry(entry: &ChangelogEntry, format: Format) -> String {
+    match format {
+        Format::AsciiDoc => asciidoc::render(entry),
+        Format::Rst => rst::render(entry),
+        _ => markdown::render(entry),
+    }
+}
+
+/// Split a changelog whose releases, categories and items are each marked by
+/// a single line (Markdown, AsciiDoc) into its release sections. Each section
+/// runs from its heading up to the next one (or the end of the file).
+fn parse_line_sections(
+    content: &str,
+    heading_regex: &Regex,
+    category_regex: &Regex,
+    item_regex: &Regex,
+    breaking_prefix: &str,
+) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut category: Option<ChangeCategory> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let text = line.trim_end();
+
+        if let Some(captures) = heading_regex.captures(text) {
+            if let Some(previous) = sections.last_mut() {
+                previous.end = start;
+            }
+            sections.push(Section {
+                entry: ChangelogEntry {
+                    version: captures[1].to_string(),
+                    date: captures
+                        .get(2)
+                        .map(|m| m.as_str().trim().to_string())
+                        .unwrap_or_default(),
+                    changes: Vec::new(),
+                },
+                start,
+                end: content.len(),
+            });
+            category = None;
+            continue;
+        }
+
+        let Some(section) = sections.last_mut() else {
+            continue;
+        };
+
+        if let Some(captures) = category_regex.captures(text) {
+            category = ChangeCategory::from_heading(&captures[1]);
+        } else if let (Some(captures), Some(category)) = (item_regex.captures(text), category) {
+            let item = &captures[1];
+            let (description, breaking) = match item.strip_prefix(breaking_prefix) {
+                Some(rest) => (rest, true),
+                None => (item, false),
+            };
+            section.entry.changes.push(Change {
+                category,
+                description: description.to_string(),
+                breaking,
+            });
+        }
+    }
+
+    sections
+}
+
+/// Parse commits into a categorized changelog entry
+fn build_entry(version: &Version, date: &str, commits: &[String]) -> ChangelogEntry {
     let mut changes: Vec<Change> = Vec::new();
     let commit_regex = Regex::new(r"^([a-z]+)(?:\([^)]+\))?(!?): (.+)$").unwrap();
 
@@ -97,72 +590,52 @@ fn generate_changelog_entry_at_path(
                 ChangeCategory::from_commit_type(commit_type)
             };
 
-            let mut desc = description.to_string();
-            if has_breaking {
-                desc = format!("**BREAKING:** {}", desc);
-            }
-
             changes.push(Change {
                 category,
-                description: desc,
+                description: description.to_string(),
+                breaking: has_breaking,
             });
         } else {
             // Fallback for commits that don't match conventional format
             changes.push(Change {
                 category: ChangeCategory::Changed,
                 description: commit.clone(),
+                breaking: false,
             });
         }
     }
 
     // Sort changes by category
-    changes.sort_by(|a, b| a.category.cmp(&b.category));
-
-    // Generate changelog content
-    let mut entry = format!("## [{}] - {}\n\n", version, date);
+    changes.sort_by_key(|c| c.category);
 
-    let mut current_category: Option<ChangeCategory> = None;
-    for change in changes {
-        if current_category.as_ref() != Some(&change.category) {
-            // Add blank line after previous list (if exists)
-            if current_category.is_some() {
-                entry.push('\n');
-            }
-            entry.push_str(&format!("{}\n\n", change.category.as_str()));
-            current_category = Some(change.category);
-        }
-        entry.push_str(&format!("- {}\n", change.description));
+    ChangelogEntry {
+        version: version.to_string(),
+        date: date.to_string(),
+        changes,
     }
-
-    // Read existing changelog or create header
-    let mut content = if changelog_path.exists() {
-        fs::read_to_string(changelog_path)?
-    } else {
-        String::from("# Changelog\n\nAll notable changes to this project will be documented in this file.\n\nThe format is based on [Keep a Changelog](https://keepachangelog.com/en/1.0.0/),\nand this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).\n\n")
-    };
-
-    // Find where to insert the new entry (after the header, before existing entries)
-    let insertion_point = if let Some(pos) = content.find("\n## [") {
-        // Add blank line after the new entry if there are existing entries
-        entry.push('\n');
-        pos + 1
-    } else {
-        content.len()
-    };
-
-    content.insert_str(insertion_point, &entry);
-
-    // Write updated changelog
-    fs::write(changelog_path, content)?;
-
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::test_repo::TestRepo;
     use tempfile::TempDir;
 
+    fn generate_changelog_entry_at_path(
+        version: &Version,
+        commits: &[String],
+        changelog_path: &Path,
+        config: &Config,
+    ) -> BumperResult<EntryUpdate> {
+        let released = release_timestamp(config, None)?;
+        let (update, file) =
+            plan_changelog_entry(version, commits, changelog_path, &released, config)?;
+        if let Some(file) = file {
+            fs::write(changelog_path, file.updated)?;
+        }
+        Ok(update)
+    }
+
     #[test]
     fn test_change_category_from_commit_type() {
         assert_eq!(
@@ -202,6 +675,7 @@ mod tests {
     fn test_generate_changelog_entry_creates_new_file() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec![
@@ -209,7 +683,7 @@ mod tests {
             "fix: resolve bug".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         assert!(changelog_path.exists());
 
@@ -242,11 +716,12 @@ mod tests {
     fn test_new_changelog_file_has_no_double_blank_lines_at_end() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec!["feat: first feature".to_string()];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -283,16 +758,17 @@ mod tests {
     fn test_generate_changelog_entry_updates_existing_file() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         // Create initial changelog
         let version1 = Version::parse("1.0.0").unwrap();
         let commits1 = vec!["feat: initial feature".to_string()];
-        generate_changelog_entry_at_path(&version1, &commits1, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version1, &commits1, &changelog_path, &config).unwrap();
 
         // Add second version
         let version2 = Version::parse("1.1.0").unwrap();
         let commits2 = vec!["feat: another feature".to_string()];
-        generate_changelog_entry_at_path(&version2, &commits2, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version2, &commits2, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -309,6 +785,7 @@ mod tests {
     fn test_generate_changelog_entry_with_breaking_changes() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("2.0.0").unwrap();
         let commits = vec![
@@ -316,7 +793,7 @@ mod tests {
             "fix: normal fix".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -330,6 +807,7 @@ mod tests {
     fn test_generate_changelog_entry_with_scopes() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec![
@@ -337,7 +815,7 @@ mod tests {
             "fix(ui): correct button alignment".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -349,6 +827,7 @@ mod tests {
     fn test_generate_changelog_entry_skips_version_bump_commits() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec![
@@ -357,7 +836,7 @@ mod tests {
             "chore: sync package version".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -370,6 +849,7 @@ mod tests {
     fn test_generate_changelog_entry_groups_by_category() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec![
@@ -379,7 +859,7 @@ mod tests {
             "feat: feature 2".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -404,6 +884,7 @@ mod tests {
     fn test_generate_changelog_entry_with_non_conventional_commits() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec![
@@ -411,7 +892,7 @@ mod tests {
             "Some random commit message".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -426,6 +907,7 @@ mod tests {
     fn test_generate_changelog_entry_multiple_categories() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec![
@@ -437,7 +919,7 @@ mod tests {
             "security: security fix".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -465,6 +947,7 @@ mod tests {
     fn test_generate_changelog_entry_markdown_lint_compliance() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec![
@@ -472,7 +955,7 @@ mod tests {
             "fix: resolve bug".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -507,21 +990,22 @@ mod tests {
     fn test_generate_changelog_entry_proper_spacing_between_releases() {
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         // Create first release
         let version1 = Version::parse("1.0.0").unwrap();
         let commits1 = vec!["feat: initial feature".to_string()];
-        generate_changelog_entry_at_path(&version1, &commits1, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version1, &commits1, &changelog_path, &config).unwrap();
 
         // Create second release
         let version2 = Version::parse("1.1.0").unwrap();
         let commits2 = vec!["fix: bug fix".to_string()];
-        generate_changelog_entry_at_path(&version2, &commits2, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version2, &commits2, &changelog_path, &config).unwrap();
 
         // Create third release
         let version3 = Version::parse("1.2.0").unwrap();
         let commits3 = vec!["feat: another feature".to_string()];
-        generate_changelog_entry_at_path(&version3, &commits3, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version3, &commits3, &changelog_path, &config).unwrap();
 
         let content = fs::read_to_string(&changelog_path).unwrap();
 
@@ -552,6 +1036,388 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_generate_changelog_entry_replaces_existing_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
+
+        let version1 = Version::parse("1.0.0").unwrap();
+        let commits1 = vec!["feat: initial feature".to_string()];
+        generate_changelog_entry_at_path(&version1, &commits1, &changelog_path, &config).unwrap();
+
+        let version2 = Version::parse("1.1.0").unwrap();
+        let commits2 = vec!["feat: first attempt".to_string()];
+        generate_changelog_entry_at_path(&version2, &commits2, &changelog_path, &config).unwrap();
+
+        // Retry of the same release with an extra commit
+        let commits_retry = vec![
+            "feat: first attempt".to_string(),
+            "fix: follow-up".to_string(),
+        ];
+        let update =
+            generate_changelog_entry_at_path(&version2, &commits_retry, &changelog_path, &config)
+                .unwrap();
+
+        assert_eq!(update, EntryUpdate::Replaced);
+
+        let content = fs::read_to_string(&changelog_path).unwrap();
+        assert_eq!(content.matches("## [1.1.0]").count(), 1);
+        assert!(content.contains("- follow-up"));
+        assert!(content.contains("- follow-up\n\n## [1.0.0]"));
+        assert!(content.contains("- initial feature"));
+        assert!(!content.contains("\n\n\n"));
+    }
+
+    #[test]
+    fn test_generate_changelog_entry_is_idempotent() {
+        let temp_dir = TempDir::new().unwrap();
+        let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
+
+        let version = Version::parse("1.0.0").unwrap();
+        let commits = vec!["feat: add feature".to_string()];
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
+        let first = fs::read_to_string(&changelog_path).unwrap();
+
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
+        let second = fs::read_to_string(&changelog_path).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_generate_changelog_entry_skips_existing_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config {
+            changelog_existing: ExistingEntry::Skip,
+            ..Config::default()
+        };
+
+        let version = Version::parse("1.0.0").unwrap();
+        let commits = vec!["feat: original".to_string()];
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
+
+        let commits_retry = vec!["feat: changed".to_string()];
+        let update =
+            generate_changelog_entry_at_path(&version, &commits_retry, &changelog_path, &config)
+                .unwrap();
+
+        assert_eq!(update, EntryUpdate::Skipped);
+        let content = fs::read_to_string(&changelog_path).unwrap();
+        assert!(content.contains("- original"));
+        assert!(!content.contains("- changed"));
+    }
+
+    #[test]
+    fn test_generate_changelog_entry_strict_mode_errors_on_existing_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config {
+            changelog_existing: ExistingEntry::Error,
+            ..Config::default()
+        };
+
+        let version = Version::parse("1.0.0").unwrap();
+        let commits = vec!["feat: original".to_string()];
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
+
+        let result = generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config);
+
+        assert!(matches!(
+            result,
+            Err(BumperError::ChangelogEntryExists(ref v, _)) if v == "1.0.0"
+        ));
+    }
+
+    #[test]
+    fn test_changelog_targets_default() {
+        let config = Config::default();
+
+        let targets = changelog_targets(&config);
+
+        assert_eq!(
+            targets,
+            vec![ChangelogTarget {
+                path: PathBuf::from("CHANGELOG.md"),
+                scope: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_changelog_targets_multiple_files() {
+        let config = Config {
+            changelog_files: vec!["CHANGELOG.md".to_string(), "docs/CHANGES.md".to_string()],
+            ..Config::default()
+        };
+
+        let targets = changelog_targets(&config);
+
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[1].path, PathBuf::from("docs/CHANGES.md"));
+        assert!(targets.iter().all(|t| t.scope.is_none()));
+    }
+
+    #[test]
+    fn test_changelog_targets_per_package() {
+        let config = Config {
+            package_files: vec![
+                "Cargo.toml".to_string(),
+                "client/Cargo.toml".to_string(),
+                "server/Cargo.toml".to_string(),
+            ],
+            changelog_per_package: true,
+            ..Config::default()
+        };
+
+        let targets = changelog_targets(&config);
+
+        assert_eq!(
+            targets,
+            vec![
+                ChangelogTarget {
+                    path: PathBuf::from("CHANGELOG.md"),
+                    scope: None,
+                },
+                ChangelogTarget {
+                    path: PathBuf::from("client/CHANGELOG.md"),
+                    scope: Some("client".to_string()),
+                },
+                ChangelogTarget {
+                    path: PathBuf::from("server/CHANGELOG.md"),
+                    scope: Some("server".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            Format::from_path(Path::new("CHANGELOG.md")),
+            Format::Markdown
+        );
+        assert_eq!(
+            Format::from_path(Path::new("docs/CHANGES")),
+            Format::Markdown
+        );
+        assert_eq!(Format::from_path(Path::new("changelog.json")), Format::Json);
+        assert_eq!(
+            Format::from_path(Path::new("docs/changelog.adoc")),
+            Format::AsciiDoc
+        );
+        assert_eq!(
+            Format::from_path(Path::new("docs/changes.rst")),
+            Format::Rst
+        );
+        assert_eq!(
+            Format::from_path(Path::new("debian/changelog")),
+            Format::Debian
+        );
+    }
+
+    #[test]
+    fn test_generate_changelog_entry_json_document() {
+        let temp_dir = TempDir::new().unwrap();
+        let changelog_path = temp_dir.path().join("changelog.json");
+        let config = Config::default();
+
+        let version1 = Version::parse("1.0.0").unwrap();
+        let commits1 = vec!["feat: initial feature".to_string()];
+        generate_changelog_entry_at_path(&version1, &commits1, &changelog_path, &config).unwrap();
+
+        let version2 = Version::parse("2.0.0").unwrap();
+        let commits2 = vec!["feat!: new api".to_string()];
+        generate_changelog_entry_at_path(&version2, &commits2, &changelog_path, &config).unwrap();
+        let update =
+            generate_changelog_entry_at_path(&version2, &commits2, &changelog_path, &config)
+                .unwrap();
+
+        assert_eq!(update, EntryUpdate::Replaced);
+
+        let content = fs::read_to_string(&changelog_path).unwrap();
+        let document: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let releases = document["releases"].as_array().unwrap();
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0]["version"], "2.0.0");
+        assert_eq!(releases[0]["changes"][0]["category"], "changed");
+        assert_eq!(releases[0]["changes"][0]["description"], "new api");
+        assert_eq!(releases[0]["changes"][0]["breaking"], true);
+        assert_eq!(releases[1]["version"], "1.0.0");
+    }
+
+    #[test]
+    fn test_generate_changelog_entry_text_formats_are_idempotent() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            debian: crate::config::DebianConfig {
+                package: Some("grubble".to_string()),
+                ..Default::default()
+            },
+            ..Config::default()
+        };
+
+        for name in ["CHANGELOG.adoc", "CHANGELOG.rst", "changelog"] {
+            let changelog_path = temp_dir.path().join(name);
+
+            let version1 = Version::parse("1.0.0").unwrap();
+            let commits1 = vec!["fix: first fix".to_string()];
+            generate_changelog_entry_at_path(&version1, &commits1, &changelog_path, &config)
+                .unwrap();
+
+            let version2 = Version::parse("1.1.0").unwrap();
+            let commits2 = vec!["feat: a feature".to_string()];
+            generate_changelog_entry_at_path(&version2, &commits2, &changelog_path, &config)
+                .unwrap();
+            let first = fs::read_to_string(&changelog_path).unwrap();
+
+            let update =
+                generate_changelog_entry_at_path(&version2, &commits2, &changelog_path, &config)
+                    .unwrap();
+            let second = fs::read_to_string(&changelog_path).unwrap();
+
+            // Debian trailers carry the time of day, which may tick between runs
+            let without_trailers = |content: &str| {
+                content
+                    .lines()
+                    .filter(|line| !line.starts_with(" -- "))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            assert_eq!(update, EntryUpdate::Replaced, "{}", name);
+            assert_eq!(
+                without_trailers(&first),
+                without_trailers(&second),
+                "{}",
+                name
+            );
+            assert!(
+                first.find("1.1.0").unwrap() < first.find("1.0.0").unwrap(),
+                "{}",
+                name
+            );
+            assert!(!first.contains("\n\n\n"), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        let utc = FixedOffset::east_opt(0);
+
+        assert_eq!(parse_timezone(None, true).unwrap(), utc);
+        assert_eq!(parse_timezone(None, false).unwrap(), None);
+        assert_eq!(parse_timezone(Some("UTC"), false).unwrap(), utc);
+        assert_eq!(parse_timezone(Some("local"), true).unwrap(), None);
+        assert_eq!(
+            parse_timezone(Some("+05:30"), false).unwrap(),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60)
+        );
+        assert_eq!(
+            parse_timezone(Some("-08:00"), false).unwrap(),
+            FixedOffset::west_opt(8 * 3600)
+        );
+        assert!(parse_timezone(Some("Europe/Paris"), false).is_err());
+    }
+
+    #[test]
+    fn test_format_date_uses_configured_format() {
+        let mut config = Config::default();
+        let released = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2025, 12, 31, 23, 30, 0)
+            .unwrap();
+
+        assert_eq!(format_date(&released, &config).unwrap(), "2025-12-31");
+
+        config.release_date.format = "%d %B %Y".to_string();
+        assert_eq!(format_date(&released, &config).unwrap(), "31 December 2025");
+
+        config.release_date.format = "%Q".to_string();
+        assert!(format_date(&released, &config).is_err());
+    }
+
+    #[test]
+    fn test_release_timestamp_from_source_date_epoch() {
+        let mut config = Config::default();
+        config.release_date.source = DateSource::SourceDateEpoch;
+        config.release_date.timezone = Some("+02:00".to_string());
+
+        // 2025-12-31T23:30:00Z, which is already the next day at +02:00
+        let released = release_timestamp(&config, Some("1767223800")).unwrap();
+
+        assert_eq!(released.timestamp(), 1767223800);
+        assert_eq!(format_date(&released, &config).unwrap(), "2026-01-01");
+        assert!(release_timestamp(&config, None).is_err());
+        assert!(release_timestamp(&config, Some("soon")).is_err());
+    }
+
+    #[test]
+    fn test_release_timestamp_from_commit() {
+        let repo = TestRepo::new();
+        repo.commit("notes.txt", "1", "feat: one");
+        let committed: i64 = repo.git(&["log", "-1", "--format=%ct"]).parse().unwrap();
+        let mut config = Config::default();
+        config.release_date.source = DateSource::Commit;
+
+        let released = release_timestamp(&config, None).unwrap();
+
+        assert_eq!(released.timestamp(), committed);
+    }
+
+    #[test]
+    fn test_render_preview_markdown_and_json() {
+        let config = Config::default();
+        let version = Version::parse("1.1.0").unwrap();
+        let commits = vec!["feat: add feature".to_string(), "fix: a bug".to_string()];
+        let released = release_timestamp(&config, None).unwrap();
+
+        let markdown =
+            render_preview(&version, &commits, Format::Markdown, &released, &config).unwrap();
+        assert!(markdown.starts_with("## [1.1.0] - "));
+        assert!(markdown.contains("### Added\n\n- add feature\n"));
+        assert!(!markdown.contains("# Changelog"));
+
+        let json = render_preview(&version, &commits, Format::Json, &released, &config).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(entry["version"], "1.1.0");
+        assert_eq!(entry["changes"][1]["category"], "fixed");
+    }
+
+    #[test]
+    fn test_render_tag_message() {
+        let mut config = Config::default();
+        config.release_date.format = "TODAY".to_string();
+        let version = Version::parse("2.0.0").unwrap();
+        let commits = vec![
+            "feat!: drop old api".to_string(),
+            "feat: add feature".to_string(),
+            "fix: a bug".to_string(),
+        ];
+        let released = release_timestamp(&config, None).unwrap();
+
+        let message =
+            render_tag_message(&version, BumpType::Major, &commits, &released, &config).unwrap();
+        assert_eq!(
+            message,
+            "v2.0.0\n\nReleased TODAY: major bump, 3 changes (1 added, 1 changed, 1 fixed, 1 breaking)."
+        );
+
+        config.release_notes = true;
+        let message =
+            render_tag_message(&version, BumpType::Major, &commits, &released, &config).unwrap();
+        assert!(message.ends_with(
+            "breaking).\n\nAdded:\n- add feature\n\nChanged:\n- BREAKING: drop old api\n\nFixed:\n- a bug"
+        ));
+
+        config.tag_message = "{version} ({bump})\n\n\n{notes}".to_string();
+        let message =
+            render_tag_message(&version, BumpType::Patch, &[], &released, &config).unwrap();
+        assert_eq!(message, "2.0.0 (patch)");
+    }
+
     #[test]
     fn test_markdown_linter_if_available() {
         use std::process::Command;
@@ -564,6 +1430,7 @@ mod tests {
 
         let temp_dir = TempDir::new().unwrap();
         let changelog_path = temp_dir.path().join("CHANGELOG.md");
+        let config = Config::default();
 
         let version = Version::parse("1.0.0").unwrap();
         let commits = vec![
@@ -572,7 +1439,7 @@ mod tests {
             "refactor: improve code".to_string(),
         ];
 
-        generate_changelog_entry_at_path(&version, &commits, &changelog_path).unwrap();
+        generate_changelog_entry_at_path(&version, &commits, &changelog_path, &config).unwrap();
 
         // Try to run markdownlint-cli if available
         let result = Command::new("npx")
@@ -0,0 +1,52 @@
+use super::{parse_line_sections, ChangelogEntry, Section};
+use regex::Regex;
+
+pub(super) const HEADER: &str =
+    "= Changelog\n\nAll notable changes to this project will be documented in this file.\n\n";
+
+const BREAKING_PREFIX: &str = "*BREAKING:* ";
+
+/// Render an entry as an AsciiDoc section, ending with a single newline
+pub(super) fn render(entry: &ChangelogEntry) -> String {
+    let mut output = format!("== {} - {}\n\n", entry.version, entry.date);
+
+    for (index, (category, changes)) in entry.grouped().iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        output.push_str(&format!("=== {}\n\n", category.name()));
+        for change in changes {
+            let prefix = if change.breaking { BREAKING_PREFIX } else { "" };
+            output.push_str(&format!("* {}{}\n", prefix, change.description));
+        }
+    }
+
+    output
+}
+
+/// Split an existing AsciiDoc changelog into its `== version` sections
+pub(super) fn parse(content: &str) -> Vec<Section> {
+    parse_line_sections(
+        content,
+        &Regex::new(r"^== (\S+)(?: - (.+))?\s*$").unwrap(),
+        &Regex::new(r"^=== (.+)$").unwrap(),
+        &Regex::new(r"^\* (.+)$").unwrap(),
+        BREAKING_PREFIX,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_round_trips_through_parse() {
+        let content =
+            "== 1.0.0 - 2025-01-01\n\n=== Changed\n\n* *BREAKING:* new api\n\n=== Fixed\n\n* fix\n";
+
+        let sections = parse(&format!("{}{}", HEADER, content));
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(render(&sections[0].entry), content);
+    }
+}
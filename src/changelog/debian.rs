@@ -0,0 +1,215 @@
+use super::{Change, ChangeCategory, ChangelogEntry, Section};
+use crate::config::Config;
+use crate::error::{BumperError, BumperResult};
+use chrono::{DateTime, FixedOffset};
+use regex::Regex;
+
+const BREAKING_PREFIX: &str = "BREAKING: ";
+
+/// Values for the `package (version) distribution; urgency=...` line and the
+/// maintainer trailer
+#[derive(Debug)]
+pub(super) struct Settings {
+    package: String,
+    distribution: String,
+    urgency: String,
+    maintainer: String,
+}
+
+impl Settings {
+    /// Combine the `debian` config section with defaults: the package name of
+    /// the newest existing entry, and the git identity as maintainer
+    pub(super) fn resolve(config: &Config, existing: &str) -> BumperResult<Self> {
+        let package = config
+            .debian
+            .package
+            .clone()
+            .or_else(|| {
+                heading_regex()
+                    .captures(existing)
+                    .map(|captures| captures[1].to_string())
+            })
+            .ok_or_else(|| {
+                BumperError::ConfigError(
+                    "debian.package is required to create a Debian changelog".to_string(),
+                )
+            })?;
+
+        let maintainer = config
+            .debian
+            .maintainer
+            .clone()
+            .unwrap_or_else(|| format!("{} <{}>", config.git_user_name, config.git_user_email));
+
+        Ok(Settings {
+            package,
+            distribution: config.debian.distribution.clone(),
+            urgency: config.debian.urgency.clone(),
+            maintainer,
+        })
+    }
+}
+
+fn heading_regex() -> Regex {
+    Regex::new(r"(?m)^(\S+) \(([^)]+)\) [^;]+;.*$").unwrap()
+}
+
+/// Render an entry in `debian/changelog` format, ending with a single newline
+pub(super) fn render(
+    entry: &ChangelogEntry,
+    released: &DateTime<FixedOffset>,
+    settings: &Settings,
+) -> String {
+    let mut output = format!(
+        "{} ({}) {}; urgency={}\n\n",
+        settings.package, entry.version, settings.distribution, settings.urgency
+    );
+
+    for change in &entry.changes {
+        let prefix = if change.breaking { BREAKING_PREFIX } else { "" };
+        output.push_str(&format!(
+            "  * {}: {}{}\n",
+            change.category.name(),
+            prefix,
+            change.description
+        ));
+    }
+
+    output.push_str(&format!(
+        "\n -- {}  {}\n",
+        settings.maintainer,
+        released.format("%a, %d %b %Y %H:%M:%S %z")
+    ));
+
+    output
+}
+
+/// Split an existing Debian changelog into its release stanzas
+pub(super) fn parse(content: &str) -> Vec<Section> {
+    let heading_regex = heading_regex();
+    let item_regex = Regex::new(r"^  \* (?:(\w+): )?(.+)$").unwrap();
+    let trailer_regex = Regex::new(r"^ -- .+>  (.+)$").unwrap();
+
+    let mut sections: Vec<Section> = Vec::new();
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let text = line.trim_end();
+
+        if let Some(captures) = heading_regex.captures(text) {
+            if let Some(previous) = sections.last_mut() {
+                previous.end = start;
+            }
+            sections.push(Section {
+                entry: ChangelogEntry {
+                    version: captures[2].to_string(),
+                    date: String::new(),
+                    changes: Vec::new(),
+                },
+                start,
+                end: content.len(),
+            });
+            continue;
+        }
+
+        let Some(section) = sections.last_mut() else {
+            continue;
+        };
+
+        if let Some(captures) = trailer_regex.captures(text) {
+            section.entry.date = captures[1].to_string();
+        } else if let Some(captures) = item_regex.captures(text) {
+            let category = captures
+                .get(1)
+                .and_then(|m| ChangeCategory::from_heading(m.as_str()))
+                .unwrap_or(ChangeCategory::Changed);
+            let item = &captures[2];
+            let (description, breaking) = match item.strip_prefix(BREAKING_PREFIX) {
+                Some(rest) => (rest, true),
+                None => (item, false),
+            };
+            section.entry.changes.push(Change {
+                category,
+                description: description.to_string(),
+                breaking,
+            });
+        }
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_render_debian_stanza() {
+        let entry = ChangelogEntry {
+            version: "1.2.0".to_string(),
+            date: "2025-01-02".to_string(),
+            changes: vec![
+                Change {
+                    category: ChangeCategory::Added,
+                    description: "new feature".to_string(),
+                    breaking: false,
+                },
+                Change {
+                    category: ChangeCategory::Changed,
+                    description: "new api".to_string(),
+                    breaking: true,
+                },
+            ],
+        };
+        let settings = Settings {
+            package: "grubble".to_string(),
+            distribution: "unstable".to_string(),
+            urgency: "medium".to_string(),
+            maintainer: "Jane Doe <jane@example.com>".to_string(),
+        };
+        let released = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2025, 1, 2, 3, 4, 5)
+            .unwrap();
+
+        let rendered = render(&entry, &released, &settings);
+
+        assert_eq!(
+            rendered,
+            "grubble (1.2.0) unstable; urgency=medium\n\n  * Added: new feature\n  * Changed: BREAKING: new api\n\n -- Jane Doe <jane@example.com>  Thu, 02 Jan 2025 03:04:05 +0000\n"
+        );
+
+        let sections = parse(&rendered);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].entry.version, "1.2.0");
+        assert_eq!(sections[0].entry.date, "Thu, 02 Jan 2025 03:04:05 +0000");
+        assert!(sections[0].entry.changes[1].breaking);
+    }
+
+    #[test]
+    fn test_resolve_takes_package_from_existing_changelog() {
+        let config = Config::default();
+        let existing = "mypkg (1.0.0) unstable; urgency=low\n\n  * Initial release\n\n -- A <a@b>  Thu, 02 Jan 2025 03:04:05 +0000\n";
+
+        let settings = Settings::resolve(&config, existing).unwrap();
+
+        assert_eq!(settings.package, "mypkg");
+        assert_eq!(
+            settings.maintainer,
+            "grubble-bot <grubble-bot@noreply.local>"
+        );
+    }
+
+    #[test]
+    fn test_resolve_requires_package_for_new_changelog() {
+        let config = Config::default();
+
+        assert!(matches!(
+            Settings::resolve(&config, ""),
+            Err(BumperError::ConfigError(_))
+        ));
+    }
+}
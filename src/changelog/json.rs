@@ -0,0 +1,55 @@
+use super::ChangelogEntry;
+use crate::error::BumperResult;
+use serde::{Deserialize, Serialize};
+
+/// Machine-readable changelog: every release, newest first
+#[derive(Serialize, Deserialize)]
+struct Document {
+    releases: Vec<ChangelogEntry>,
+}
+
+pub(super) fn parse(content: &str) -> BumperResult<Vec<ChangelogEntry>> {
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let document: Document = serde_json::from_str(content)?;
+    Ok(document.releases)
+}
+
+pub(super) fn render_document(releases: Vec<ChangelogEntry>) -> BumperResult<String> {
+    let rendered = serde_json::to_string_pretty(&Document { releases })?;
+    Ok(format!("{}\n", rendered))
+}
+
+/// Render a single entry as a standalone JSON object
+pub(super) fn render_entry(entry: &ChangelogEntry) -> BumperResult<String> {
+    Ok(format!("{}\n", serde_json::to_string_pretty(entry)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Change, ChangeCategory};
+    use super::*;
+
+    #[test]
+    fn test_document_round_trip() {
+        let entries = vec![ChangelogEntry {
+            version: "1.0.0".to_string(),
+            date: "2025-01-01".to_string(),
+            changes: vec![Change {
+                category: ChangeCategory::Added,
+                description: "feature".to_string(),
+                breaking: true,
+            }],
+        }];
+
+        let rendered = render_document(entries).unwrap();
+        let parsed = parse(&rendered).unwrap();
+
+        assert!(rendered.contains("\"category\": \"added\""));
+        assert!(rendered.contains("\"breaking\": true"));
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].version, "1.0.0");
+        assert!(parsed[0].changes[0].breaking);
+    }
+}
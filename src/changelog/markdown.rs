@@ -0,0 +1,72 @@
+use super::{parse_line_sections, ChangelogEntry, Section};
+use regex::Regex;
+
+pub(super) const HEADER: &str = "# Changelog\n\nAll notable changes to this project will be documented in this file.\n\nThe format is based on [Keep a Changelog](https://keepachangelog.com/en/1.0.0/),\nand this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).\n\n";
+
+const BREAKING_PREFIX: &str = "**BREAKING:** ";
+
+/// Render an entry as a Keep a Changelog section, ending with a single newline
+pub(super) fn render(entry: &ChangelogEntry) -> String {
+    let mut output = format!("## [{}] - {}\n\n", entry.version, entry.date);
+
+    for (index, (category, changes)) in entry.grouped().iter().enumerate() {
+        // Add blank line after previous list (if exists)
+        if index > 0 {
+            output.push('\n');
+        }
+        output.push_str(&format!("### {}\n\n", category.name()));
+        for change in changes {
+            let prefix = if change.breaking { BREAKING_PREFIX } else { "" };
+            output.push_str(&format!("- {}{}\n", prefix, change.description));
+        }
+    }
+
+    output
+}
+
+/// Split an existing Markdown changelog into its release sections.
+/// Each section runs from its `## [version]` heading up to the next one
+/// (or the end of the file).
+pub(super) fn parse(content: &str) -> Vec<Section> {
+    parse_line_sections(
+        content,
+        &Regex::new(r"^## \[([^\]]+)\](?: - (.+))?\s*$").unwrap(),
+        &Regex::new(r"^### (.+)$").unwrap(),
+        &Regex::new(r"^[-*] (.+)$").unwrap(),
+        BREAKING_PREFIX,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ChangeCategory;
+    use super::*;
+
+    #[test]
+    fn test_parse_reads_existing_entries() {
+        let content = "# Changelog\n\n## [1.1.0] - 2025-01-02\n\n### Added\n\n- second\n- **BREAKING:** third\n\n## [1.0.0] - 2025-01-01\n\n### Fixed\n\n- first\n";
+
+        let sections = parse(content);
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].entry.version, "1.1.0");
+        assert_eq!(sections[0].entry.date, "2025-01-02");
+        assert_eq!(sections[0].entry.changes.len(), 2);
+        assert_eq!(sections[0].entry.changes[0].category, ChangeCategory::Added);
+        assert!(sections[0].entry.changes[1].breaking);
+        assert_eq!(sections[0].entry.changes[1].description, "third");
+        assert_eq!(sections[1].entry.version, "1.0.0");
+        assert_eq!(sections[1].entry.changes[0].category, ChangeCategory::Fixed);
+        assert_eq!(sections[0].end, sections[1].start);
+        assert_eq!(sections[1].end, content.len());
+    }
+
+    #[test]
+    fn test_render_round_trips_through_parse() {
+        let content = "## [1.0.0] - 2025-01-01\n\n### Added\n\n- feature\n\n### Fixed\n\n- fix\n";
+
+        let sections = parse(content);
+
+        assert_eq!(render(&sections[0].entry), content);
+    }
+}
@@ -0,0 +1,99 @@
+use super::{Change, ChangeCategory, ChangelogEntry, Section};
+
+pub(super) const HEADER: &str = "Changelog\n=========\n\nAll notable changes to this project will be documented in this file.\n\n";
+
+const BREAKING_PREFIX: &str = "**BREAKING:** ";
+
+/// Render an entry as a reStructuredText section, ending with a single newline
+pub(super) fn render(entry: &ChangelogEntry) -> String {
+    let title = format!("{} - {}", entry.version, entry.date);
+    let mut output = format!("{}\n{}\n\n", title, "-".repeat(title.len()));
+
+    for (index, (category, changes)) in entry.grouped().iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        let name = category.name();
+        output.push_str(&format!("{}\n{}\n\n", name, "~".repeat(name.len())));
+        for change in changes {
+            let prefix = if change.breaking { BREAKING_PREFIX } else { "" };
+            output.push_str(&format!("- {}{}\n", prefix, change.description));
+        }
+    }
+
+    output
+}
+
+/// Split an existing reStructuredText changelog into its release sections,
+/// which are titles underlined with `-`
+pub(super) fn parse(content: &str) -> Vec<Section> {
+    let lines: Vec<(usize, &str)> = content
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line.trim_end()))
+        })
+        .collect();
+
+    let underlined = |index: usize, marker: char| {
+        let title = lines[index].1;
+        lines.get(index + 1).is_some_and(|(_, next)| {
+            !title.is_empty() && next.len() >= title.len() && next.chars().all(|c| c == marker)
+        })
+    };
+
+    let mut sections: Vec<Section> = Vec::new();
+    let mut category: Option<ChangeCategory> = None;
+
+    for (index, (start, text)) in lines.iter().enumerate() {
+        if underlined(index, '-') {
+            if let Some(previous) = sections.last_mut() {
+                previous.end = *start;
+            }
+            let (version, date) = text.split_once(" - ").unwrap_or((text, ""));
+            sections.push(Section {
+                entry: ChangelogEntry {
+                    version: version.trim().to_string(),
+                    date: date.trim().to_string(),
+                    changes: Vec::new(),
+                },
+                start: *start,
+                end: content.len(),
+            });
+            category = None;
+        } else if underlined(index, '~') {
+            category = ChangeCategory::from_heading(text);
+        } else if let (Some(section), Some(category), Some(item)) =
+            (sections.last_mut(), category, text.strip_prefix("- "))
+        {
+            let (description, breaking) = match item.strip_prefix(BREAKING_PREFIX) {
+                Some(rest) => (rest, true),
+                None => (item, false),
+            };
+            section.entry.changes.push(Change {
+                category,
+                description: description.to_string(),
+                breaking,
+            });
+        }
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_round_trips_through_parse() {
+        let content = "1.0.0 - 2025-01-01\n------------------\n\nAdded\n~~~~~\n\n- feature\n\nFixed\n~~~~~\n\n- **BREAKING:** fix\n";
+
+        let sections = parse(&format!("{}{}", HEADER, content));
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].entry.version, "1.0.0");
+        assert_eq!(render(&sections[0].entry), content);
+    }
+}
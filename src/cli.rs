@@ -0,0 +1,590 @@
+use crate::config::{Origin, Override};
+use crate::versioner::ReleaseAs;
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(name = "grubble", version)]
+#[command(about = "Automatic semantic versioning based on conventional commits", long_about = None)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Output format: human-readable text or a single JSON document on stdout
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
+    /// Read the config from this file instead of searching for one
+    #[arg(long = "config", global = true, value_name = "PATH")]
+    pub config_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[command(
+        flatten,
+        next_help_heading = "Deprecated options (use `grubble bump` or `grubble next`)"
+    )]
+    pub legacy: LegacyArgs,
+}
+
+impl Cli {
+    /// Parse the command line. The deprecated flags sit next to the
+    /// subcommands, so clap alone accepts `grubble --tag bump` and the
+    /// subcommand would silently ignore `--tag`; that is rejected here.
+    pub fn try_parse_checked<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let cli = Self::try_parse_from(args)?;
+        if let (Some(command), Some(flag)) = (&cli.command, cli.legacy.first_flag()) {
+            return Err(Self::command().error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "the subcommand '{}' cannot be used with '{}'; pass options after the subcommand",
+                    command.name(),
+                    flag
+                ),
+            ));
+        }
+        Ok(cli)
+    }
+
+    /// The release options, for `bump` and the deprecated form without a subcommand
+    pub fn bump_args(&self) -> Option<&BumpArgs> {
+        match &self.command {
+            Some(Command::Bump(args)) => Some(args),
+            None => Some(&self.legacy.bump),
+            _ => None,
+        }
+    }
+
+    /// The options deciding the next version, for the commands that work it out
+    pub fn release_args(&self) -> Option<&ReleaseArgs> {
+        match &self.command {
+            Some(Command::Next(args)) | Some(Command::Explain(args)) => Some(args),
+            Some(Command::Changelog { release, .. }) => Some(release),
+            Some(Command::Bump(args)) => Some(&args.release),
+            None => Some(&self.legacy.bump.release),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print the current version
+    Current,
+
+    /// Print the next version and bump type without changing anything
+    Next(ReleaseArgs),
+
+    /// Show how each commit since the last release was classified and why
+    Explain(ReleaseArgs),
+
+    /// Bump the version, update files and optionally tag and push
+    Bump(BumpArgs),
+
+    /// Print the changelog entry for the pending commits without changing anything
+    Changelog {
+        /// Output format of the entry
+        #[arg(long, value_enum, default_value_t = PreviewFormat::Markdown)]
+        format: PreviewFormat,
+
+        #[command(flatten)]
+        release: ReleaseArgs,
+    },
+
+    /// Check that the configuration and repository are ready for a release
+    Verify,
+
+    /// Inspect or explain the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// Inspect the repository and create a starter .versionrc.json
+    Init {
+        /// Overwrite an existing .versionrc.json
+        #[arg(long)]
+        force: bool,
+
+        /// Accept the detected settings without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Current => "current",
+            Command::Next(_) => "next",
+            Command::Explain(_) => "explain",
+            Command::Bump(_) => "bump",
+            Command::Changelog { .. } => "changelog",
+            Command::Verify => "verify",
+            Command::Config(_) => "config",
+            Command::Init { .. } => "init",
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective configuration and where each value came from
+    Show,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum PreviewFormat {
+    Markdown,
+    Json,
+}
+
+/// Options that override the config file, accepted by every subcommand
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    /// Versioning strategy (node, rust, git)
+    #[arg(long, global = true)]
+    pub preset: Option<String>,
+
+    /// Prefix for git tags (default: v)
+    #[arg(long, global = true)]
+    pub tag_prefix: Option<String>,
+
+    /// Prefix for commit messages
+    #[arg(long, global = true)]
+    pub commit_prefix: Option<String>,
+
+    /// Comma-separated list of files to update (for node/rust preset)
+    #[arg(long, global = true)]
+    pub package_files: Option<String>,
+
+    /// Git user name for commits
+    #[arg(long, global = true)]
+    pub git_user_name: Option<String>,
+
+    /// Git user email for commits
+    #[arg(long, global = true)]
+    pub git_user_email: Option<String>,
+
+    /// Comma-separated list of changelog files to maintain (default: CHANGELOG.md)
+    #[arg(long, global = true)]
+    pub changelog_files: Option<String>,
+
+    /// Write a separate changelog next to each package file
+    #[arg(long, global = true, overrides_with = "no_changelog_per_package")]
+    pub changelog_per_package: bool,
+
+    /// Turn off changelogPerPackage from the config
+    #[arg(long, global = true)]
+    pub no_changelog_per_package: bool,
+
+    /// Set any config key, e.g. `releaseDate.source=commit` or `signing.commits=false` (repeatable)
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = parse_assignment)]
+    pub set: Vec<(String, String)>,
+
+    /// Set the bump for a commit type, e.g. `config=patch` (repeatable)
+    #[arg(long = "type", global = true, value_name = "TYPE=BUMP", value_parser = parse_assignment)]
+    pub types: Vec<(String, String)>,
+}
+
+/// Options controlling what a release does. Every flag has a `--no-` form
+/// that turns off a setting enabled in the config.
+#[derive(Args, Debug)]
+pub struct BumpArgs {
+    /// Push changes to remote
+    #[arg(short, long, overrides_with = "no_push")]
+    pub push: bool,
+
+    /// Don't push, even if the config enables it
+    #[arg(long)]
+    pub no_push: bool,
+
+    /// Remote to push to (default: origin)
+    #[arg(long, value_name = "NAME")]
+    pub remote: Option<String>,
+
+    /// Branch on the remote to push the release commit to (default: the current branch)
+    #[arg(long, value_name = "BRANCH")]
+    pub remote_branch: Option<String>,
+
+    /// Suppress commit list output
+    #[arg(short, long, overrides_with = "no_quiet")]
+    pub quiet: bool,
+
+    /// List commits, even if the config sets quiet
+    #[arg(long)]
+    pub no_quiet: bool,
+
+    /// Create git tag for the version
+    #[arg(short, long, overrides_with = "no_tag")]
+    pub tag: bool,
+
+    /// Don't create a tag, even if the config enables it
+    #[arg(long)]
+    pub no_tag: bool,
+
+    /// Include release notes in the git tag annotation
+    #[arg(short = 'r', long, overrides_with = "no_release_notes")]
+    pub release_notes: bool,
+
+    /// Leave release notes out of the tag annotation
+    #[arg(long)]
+    pub no_release_notes: bool,
+
+    /// Update major version tag (e.g., v4 -> v4.x.x)
+    #[arg(long, overrides_with = "no_update_major_tag")]
+    pub update_major_tag: bool,
+
+    /// Don't update the major version tag
+    #[arg(long)]
+    pub no_update_major_tag: bool,
+
+    /// Update minor version tag (e.g., v4.1 -> v4.1.x)
+    #[arg(long, overrides_with = "no_update_minor_tag")]
+    pub update_minor_tag: bool,
+
+    /// Don't update the minor version tag
+    #[arg(long)]
+    pub no_update_minor_tag: bool,
+
+    /// Generate and maintain a CHANGELOG.md file
+    #[arg(long, overrides_with = "no_changelog")]
+    pub changelog: bool,
+
+    /// Don't update the changelog, even if the config enables it
+    #[arg(long)]
+    pub no_changelog: bool,
+
+    /// Sign the bump commit and release tag
+    #[arg(long, overrides_with = "no_sign")]
+    pub sign: bool,
+
+    /// Don't sign, even if the config enables it
+    #[arg(long)]
+    pub no_sign: bool,
+
+    #[command(flatten)]
+    pub release: ReleaseArgs,
+
+    /// Print the file diffs and git commands a release would run, without running them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Options deciding the next version, shared by the commands that work it out
+#[derive(Args, Debug, Clone, Default)]
+pub struct ReleaseArgs {
+    /// Release this exact version, or at least this bump level
+    #[arg(long, value_name = "VERSION|major|minor|patch")]
+    pub release_as: Option<ReleaseAs>,
+}
+
+/// Flags accepted without a subcommand, kept as aliases of `grubble bump`
+/// until the next major version
+#[derive(Args, Debug)]
+pub struct LegacyArgs {
+    #[command(flatten)]
+    pub bump: BumpArgs,
+
+    /// Output only the new version string (dry run, no changes)
+    #[arg(long)]
+    pub raw: bool,
+}
+
+impl LegacyArgs {
+    /// The first deprecated flag given, if any
+    fn first_flag(&self) -> Option<String> {
+        let flags = [
+            (self.raw, "--raw"),
+            (self.bump.dry_run, "--dry-run"),
+            (self.bump.release.release_as.is_some(), "--release-as"),
+        ];
+        if let Some((_, flag)) = flags.iter().find(|(given, _)| *given) {
+            return Some(flag.to_string());
+        }
+        self.bump
+            .overrides()
+            .into_iter()
+            .find_map(|item| match item.origin {
+                Origin::Cli(flag) => Some(flag),
+                _ => None,
+            })
+    }
+}
+
+fn parse_assignment(text: &str) -> Result<(String, String), String> {
+    match text.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got `{}`", text)),
+    }
+}
+
+/// The value a `--flag`/`--no-flag` pair sets, if either was given
+fn switch(on: bool, off: bool) -> Option<&'static str> {
+    match (on, off) {
+        (true, _) => Some("true"),
+        (_, true) => Some("false"),
+        _ => None,
+    }
+}
+
+/// Overrides for `--flag`/`--no-flag` pairs, named after the `--flag` form
+fn switches(pairs: &[(&str, bool, bool, &str)]) -> Vec<Override> {
+    pairs
+        .iter()
+        .filter_map(|&(key, on, off, flag)| {
+            let value = switch(on, off)?;
+            let flag = if on {
+                format!("--{}", flag)
+            } else {
+                format!("--no-{}", flag)
+            };
+            Some(Override::new(key, value, &flag))
+        })
+        .collect()
+}
+
+impl ConfigArgs {
+    pub fn overrides(&self) -> Vec<Override> {
+        let mut overrides = Vec::new();
+        let values = [
+            ("preset", &self.preset, "--preset"),
+            ("tagPrefix", &self.tag_prefix, "--tag-prefix"),
+            ("commitPrefix", &self.commit_prefix, "--commit-prefix"),
+            ("packageFiles", &self.package_files, "--package-files"),
+            ("gitUserName", &self.git_user_name, "--git-user-name"),
+            ("gitUserEmail", &self.git_user_email, "--git-user-email"),
+            ("changelogFiles", &self.changelog_files, "--changelog-files"),
+        ];
+        for (key, value, flag) in values {
+            if let Some(value) = value {
+                overrides.push(Override::new(key, value, flag));
+            }
+        }
+        overrides.extend(switches(&[(
+            "changelogPerPackage",
+            self.changelog_per_package,
+            self.no_changelog_per_package,
+            "changelog-per-package",
+        )]));
+        for (key, value) in &self.set {
+            overrides.push(Override::new(key, value, &format!("--set {}", key)));
+        }
+        for (commit_type, bump) in &self.types {
+            overrides.push(Override::new(
+                &format!("types.{}", commit_type),
+                bump,
+                &format!("--type {}", commit_type),
+            ));
+        }
+        overrides
+    }
+}
+
+impl BumpArgs {
+    pub fn overrides(&self) -> Vec<Override> {
+        let mut overrides = Vec::new();
+        let values = [
+            ("remote", &self.remote, "--remote"),
+            ("remoteBranch", &self.remote_branch, "--remote-branch"),
+        ];
+        for (key, value, flag) in values {
+            if let Some(value) = value {
+                overrides.push(Override::new(key, value, flag));
+            }
+        }
+        overrides.extend(switches(&[
+            ("push", self.push, self.no_push, "push"),
+            ("quiet", self.quiet, self.no_quiet, "quiet"),
+            ("tag", self.tag, self.no_tag, "tag"),
+            (
+                "releaseNotes",
+                self.release_notes,
+                self.no_release_notes,
+                "release-notes",
+            ),
+            (
+                "updateMajorTag",
+                self.update_major_tag,
+                self.no_update_major_tag,
+                "update-major-tag",
+            ),
+            (
+                "updateMinorTag",
+                self.update_minor_tag,
+                self.no_update_minor_tag,
+                "update-minor-tag",
+            ),
+            ("changelog", self.changelog, self.no_changelog, "changelog"),
+            ("signing.commits", self.sign, self.no_sign, "sign"),
+            ("signing.tags", self.sign, self.no_sign, "sign"),
+        ]));
+        overrides
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyser::BumpType;
+
+    #[test]
+    fn test_subcommands_accept_global_config_flags() {
+        let cli = Cli::try_parse_from(["grubble", "next", "--tag-prefix", "release-"]).unwrap();
+
+        assert!(matches!(cli.command, Some(Command::Next(_))));
+        assert_eq!(cli.config.tag_prefix.as_deref(), Some("release-"));
+    }
+
+    #[test]
+    fn test_bump_subcommand_flags() {
+        let cli = Cli::try_parse_from(["grubble", "bump", "--tag", "--push", "-q"]).unwrap();
+
+        let Some(Command::Bump(args)) = cli.command else {
+            panic!("expected bump subcommand");
+        };
+        assert!(args.tag && args.push && args.quiet);
+        assert!(!args.changelog);
+    }
+
+    #[test]
+    fn test_legacy_flags_without_subcommand() {
+        let cli = Cli::try_parse_from(["grubble", "--tag", "--raw", "--preset", "rust"]).unwrap();
+
+        assert!(cli.command.is_none());
+        assert!(cli.legacy.bump.tag);
+        assert!(cli.legacy.raw);
+        assert_eq!(cli.config.preset.as_deref(), Some("rust"));
+    }
+
+    #[test]
+    fn test_legacy_flags_conflict_with_subcommands() {
+        let error = Cli::try_parse_checked(["grubble", "--tag", "bump"]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+        assert!(error
+            .to_string()
+            .contains("the subcommand 'bump' cannot be used with '--tag'"));
+
+        let error = Cli::try_parse_checked(["grubble", "--raw", "next"]).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("'next' cannot be used with '--raw'"));
+
+        assert!(Cli::try_parse_checked(["grubble", "--no-push", "bump"]).is_err());
+        assert!(Cli::try_parse_checked(["grubble", "--tag", "--raw"]).is_ok());
+        assert!(Cli::try_parse_checked(["grubble", "bump", "--tag"]).is_ok());
+        assert!(Cli::try_parse_checked(["grubble", "--output", "json", "current"]).is_ok());
+    }
+
+    #[test]
+    fn test_output_flag_after_subcommand() {
+        let cli = Cli::try_parse_from(["grubble", "bump", "--output", "json"]).unwrap();
+        assert_eq!(cli.output, OutputFormat::Json);
+
+        let cli = Cli::try_parse_from(["grubble", "current"]).unwrap();
+        assert_eq!(cli.output, OutputFormat::Text);
+    }
+
+    #[test]
+    fn test_release_as_flag() {
+        let cli = Cli::try_parse_from(["grubble", "bump", "--release-as", "1.0.0"]).unwrap();
+
+        let Some(Command::Bump(args)) = cli.command else {
+            panic!("expected bump subcommand");
+        };
+        assert!(matches!(
+            args.release.release_as,
+            Some(ReleaseAs::Version(_))
+        ));
+        assert!(Cli::try_parse_from(["grubble", "bump", "--release-as", "next"]).is_err());
+
+        for command in ["next", "explain", "changelog"] {
+            let cli = Cli::try_parse_from(["grubble", command, "--release-as", "minor"]).unwrap();
+            assert!(matches!(
+                cli.release_args().and_then(|args| args.release_as.clone()),
+                Some(ReleaseAs::Bump(BumpType::Minor))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_overrides_use_config_keys() {
+        let cli = Cli::try_parse_from([
+            "grubble",
+            "bump",
+            "--tag",
+            "--package-files",
+            "a/Cargo.toml,b/Cargo.toml",
+        ])
+        .unwrap();
+        let Some(Command::Bump(args)) = &cli.command else {
+            panic!("expected bump subcommand");
+        };
+
+        assert_eq!(
+            cli.config.overrides(),
+            vec![Override::new(
+                "packageFiles",
+                "a/Cargo.toml,b/Cargo.toml",
+                "--package-files"
+            )]
+        );
+        assert_eq!(
+            args.overrides(),
+            vec![Override::new("tag", "true", "--tag")]
+        );
+    }
+
+    #[test]
+    fn test_negated_flags_and_assignments() {
+        let cli = Cli::try_parse_from([
+            "grubble",
+            "bump",
+            "--push",
+            "--no-push",
+            "--no-changelog",
+            "--set",
+            "releaseDate.source=commit",
+            "--type",
+            "config=patch",
+        ])
+        .unwrap();
+        let Some(Command::Bump(args)) = &cli.command else {
+            panic!("expected bump subcommand");
+        };
+
+        assert_eq!(
+            args.overrides(),
+            vec![
+                Override::new("push", "false", "--no-push"),
+                Override::new("changelog", "false", "--no-changelog"),
+            ]
+        );
+        assert_eq!(
+            cli.config.overrides(),
+            vec![
+                Override::new("releaseDate.source", "commit", "--set releaseDate.source"),
+                Override::new("types.config", "patch", "--type config"),
+            ]
+        );
+        assert!(Cli::try_parse_from(["grubble", "next", "--set", "tag"]).is_err());
+    }
+
+    #[test]
+    fn test_raw_is_not_a_bump_flag() {
+        assert!(Cli::try_parse_from(["grubble", "bump", "--raw"]).is_err());
+    }
+}
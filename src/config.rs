@@ -1,7 +1,20 @@
+use crate::branch::BranchPolicy;
+use crate::changelog::TAG_PLACEHOLDERS;
+use crate::error::{BumperError, BumperResult};
+use crate::versioner::{ReleaseAs, VersionRange};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+
+mod layers;
+mod source;
+
+pub use layers::{ConfigLayers, Origin, Override};
+pub use source::ConfigSource;
+use source::Located;
+
+pub const CONFIG_FILE: &str = ".versionrc.json";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,9 +31,30 @@ pub struct Config {
     #[serde(default)]
     pub push: bool,
 
+    /// Remote the release is pushed to
+    #[serde(default = "default_remote")]
+    pub remote: String,
+
+    /// Branch on the remote that receives the release commit (default: the
+    /// checked-out branch)
+    #[serde(default)]
+    pub remote_branch: Option<String>,
+
     #[serde(default)]
     pub tag: bool,
 
+    /// Whether release tags are annotated or lightweight
+    #[serde(default)]
+    pub tag_style: TagStyle,
+
+    /// Template for the annotation of release tags
+    #[serde(default = "default_tag_message")]
+    pub tag_message: String,
+
+    /// Don't list the commits included in a release
+    #[serde(default)]
+    pub quiet: bool,
+
     #[serde(default = "default_preset")]
     pub preset: String,
 
@@ -48,14 +82,272 @@ pub struct Config {
     #[serde(default)]
     pub changelog: bool,
 
+    /// Changelog files to maintain, relative to the repository root
+    #[serde(default = "default_changelog_files")]
+    pub changelog_files: Vec<String>,
+
+    /// Write a separate changelog next to each package file
+    #[serde(default)]
+    pub changelog_per_package: bool,
+
+    /// What to do when the changelog already has an entry for the version
+    #[serde(default)]
+    pub changelog_existing: ExistingEntry,
+
+    /// How the release date in changelog entries is determined
+    #[serde(default)]
+    pub release_date: ReleaseDateConfig,
+
+    /// Settings for `debian/changelog` output
+    #[serde(default)]
+    pub debian: DebianConfig,
+
+    /// Bump rules while the major version is 0
+    #[serde(default)]
+    pub initial_development: InitialDevelopmentConfig,
+
+    /// Which branches may release and what they produce
+    #[serde(default)]
+    pub branches: BranchesConfig,
+
+    /// Sign bump commits and release tags
+    #[serde(default)]
+    pub signing: SigningConfig,
+
+    /// What to do in a shallow clone where the last release tag is out of reach
+    #[serde(default)]
+    pub shallow_clone: ShallowClone,
+
+    /// Set by `--release-as`, overriding the bump computed from commits
     #[serde(skip)]
-    pub raw: bool,
+    pub release_as: Option<ReleaseAs>,
+
+    /// Rules for the checked-out branch, resolved from `branches`
+    #[serde(skip)]
+    pub branch: BranchPolicy,
+}
+
+/// Behaviour when a changelog entry for the release version already exists,
+/// e.g. when a release is retried after a failed push
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExistingEntry {
+    /// Regenerate the entry in place
+    #[default]
+    Replace,
+    /// Leave the existing entry untouched
+    Skip,
+    /// Fail the release
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TagStyle {
+    /// A tag object with a message from `tagMessage`
+    #[default]
+    Annotated,
+    /// A bare ref to the release commit
+    Lightweight,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShallowClone {
+    /// Stop with an error explaining how to fetch the history
+    #[default]
+    Fail,
+    /// Fetch tags and deepen the history until the last release tag is reachable
+    Deepen,
+}
+
+/// Where the release timestamp comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DateSource {
+    /// The time grubble runs
+    #[default]
+    Now,
+    /// The committer date of the commit being released
+    Commit,
+    /// The `SOURCE_DATE_EPOCH` environment variable, for reproducible output
+    SourceDateEpoch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseDateConfig {
+    #[serde(default)]
+    pub source: DateSource,
+
+    /// `"utc"`, `"local"` or a fixed offset such as `"+02:00"`
+    /// (default: UTC when running in CI, local time otherwise)
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// strftime-style format of the date shown in changelog entries
+    #[serde(default = "default_date_format")]
+    pub format: String,
+}
+
+impl Default for ReleaseDateConfig {
+    fn default() -> Self {
+        ReleaseDateConfig {
+            source: DateSource::default(),
+            timezone: None,
+            format: default_date_format(),
+        }
+    }
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+/// SemVer leaves 0.x versions open to breaking changes, so many projects
+/// bump one level lower until they release 1.0.0
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitialDevelopmentConfig {
+    /// Breaking changes bump the minor version instead of releasing 1.0.0
+    #[serde(default)]
+    pub breaking_is_minor: bool,
+
+    /// Minor-level changes such as `feat` bump the patch version
+    #[serde(default)]
+    pub features_are_patch: bool,
+}
+
+/// Release rules per branch. With no rules every branch releases normally.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchesConfig {
+    /// Checked in order; the first rule whose name matches applies
+    #[serde(default)]
+    pub rules: Vec<BranchRule>,
+
+    /// What happens on branches no rule matches
+    #[serde(default)]
+    pub other: OtherBranches,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BranchRule {
+    /// Branch name, where `*` matches any characters, e.g. `release/*`
+    pub name: String,
+
+    /// Release prereleases on this channel, e.g. `next` for `2.0.0-next.1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prerelease: Option<String>,
+
+    /// Only release versions in this range, e.g. `1.x`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OtherBranches {
+    /// Fail the release
+    #[default]
+    Refuse,
+    /// Print what a release would do instead of doing it
+    DryRun,
+}
+
+/// Signing of bump commits and release tags with a GPG, SSH or X.509 key.
+/// Unset values fall back to git's own signing config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningConfig {
+    /// Sign the bump commit (`git commit -S`)
+    #[serde(default)]
+    pub commits: bool,
+
+    /// Sign release tags (`git tag -s`)
+    #[serde(default)]
+    pub tags: bool,
+
+    /// Key id, or for SSH the key file (default: git's `user.signingKey`)
+    #[serde(default)]
+    pub key: Option<String>,
+
+    /// Key type (default: git's `gpg.format`)
+    #[serde(default)]
+    pub format: Option<SigningFormat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningFormat {
+    Openpgp,
+    Ssh,
+    X509,
+}
+
+impl SigningFormat {
+    /// The value of git's `gpg.format`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SigningFormat::Openpgp => "openpgp",
+            SigningFormat::Ssh => "ssh",
+            SigningFormat::X509 => "x509",
+        }
+    }
+}
+
+/// Fields of the `debian/changelog` stanza that can't be derived from commits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebianConfig {
+    /// Source package name (default: taken from the existing changelog)
+    #[serde(default)]
+    pub package: Option<String>,
+
+    #[serde(default = "default_debian_distribution")]
+    pub distribution: String,
+
+    #[serde(default = "default_debian_urgency")]
+    pub urgency: String,
+
+    /// `Name <email>` (default: gitUserName and gitUserEmail)
+    #[serde(default)]
+    pub maintainer: Option<String>,
+}
+
+impl Default for DebianConfig {
+    fn default() -> Self {
+        DebianConfig {
+            package: None,
+            distribution: default_debian_distribution(),
+            urgency: default_debian_urgency(),
+            maintainer: None,
+        }
+    }
+}
+
+fn default_debian_distribution() -> String {
+    "unstable".to_string()
+}
+
+fn default_debian_urgency() -> String {
+    "medium".to_string()
 }
 
 fn default_package_files() -> Vec<String> {
     vec![]
 }
 
+/// Package files a preset updates unless `packageFiles` is set
+fn preset_package_files(preset: &str) -> Vec<String> {
+    match preset {
+        "rust" => vec!["Cargo.toml".to_string()],
+        "node" => vec!["package.json".to_string()],
+        _ => vec![],
+    }
+}
+
 fn default_commit_prefix() -> String {
     "chore: bump version".to_string()
 }
@@ -64,6 +356,14 @@ fn default_tag_prefix() -> String {
     "v".to_string()
 }
 
+fn default_remote() -> String {
+    "origin".to_string()
+}
+
+fn default_tag_message() -> String {
+    "{tag}\n\nReleased {date}: {bump} bump, {summary}.\n\n{notes}".to_string()
+}
+
 fn default_preset() -> String {
     "git".to_string()
 }
@@ -76,6 +376,10 @@ fn default_git_user_email() -> String {
     "grubble-bot@noreply.local".to_string()
 }
 
+fn default_changelog_files() -> Vec<String> {
+    vec!["CHANGELOG.md".to_string()]
+}
+
 fn default_types() -> HashMap<String, String> {
     let mut types = HashMap::new();
     types.insert("feat".to_string(), "minor".to_string());
@@ -98,7 +402,12 @@ impl Default for Config {
             commit_prefix: default_commit_prefix(),
             tag_prefix: default_tag_prefix(),
             push: false,
+            remote: default_remote(),
+            remote_branch: None,
             tag: false,
+            tag_style: TagStyle::default(),
+            tag_message: default_tag_message(),
+            quiet: false,
             preset: default_preset(),
             release_notes: false,
             git_user_name: default_git_user_name(),
@@ -107,25 +416,448 @@ impl Default for Config {
             update_major_tag: false,
             update_minor_tag: false,
             changelog: false,
-            raw: false,
+            changelog_files: default_changelog_files(),
+            changelog_per_package: false,
+            changelog_existing: ExistingEntry::default(),
+            release_date: ReleaseDateConfig::default(),
+            debian: DebianConfig::default(),
+            initial_development: InitialDevelopmentConfig::default(),
+            branches: BranchesConfig::default(),
+            signing: SigningConfig::default(),
+            shallow_clone: ShallowClone::default(),
+            release_as: None,
+            branch: BranchPolicy::default(),
+        }
+    }
+}
+
+/// Blank out `//` and `/* */` comments outside strings, keeping line breaks so
+/// positions in parse errors still match the file
+pub fn strip_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                    }
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            _ => out.push(c),
         }
     }
+
+    out
 }
 
+pub const PRESETS: [&str; 3] = ["git", "node", "rust"];
+pub const BUMP_LEVELS: [&str; 4] = ["major", "minor", "patch", "none"];
+
+/// Objects whose keys are user-defined, so unknown keys are allowed
+const OPEN_OBJECTS: [&str; 1] = ["types"];
+
 impl Config {
-    pub fn load() -> Self {
-        Self::load_from_path(".versionrc.json")
+    /// Read and validate the config in `source`, `None` if it is a manifest
+    /// without a grubble section. Every problem is reported with its line
+    /// and column.
+    fn read_file(source: &ConfigSource) -> BumperResult<Option<serde_json::Value>> {
+        let name = source.path.display().to_string();
+        let content = source.prepare(&fs::read_to_string(&source.path)?);
+        let at =
+            |(line, column, message): Located| format!("{}:{}:{}: {}", name, line, column, message);
+
+        let Some(value) = source
+            .parse_value(&content)
+            .map_err(|e| BumperError::ConfigError(at(e)))?
+        else {
+            return Ok(None);
+        };
+
+        let known = serde_json::to_value(Config::default())?;
+        let mut problems = Vec::new();
+        unknown_keys(&value, &known, "", &mut problems);
+
+        let parsed = source.parse_config(&content);
+        if let Ok(Some(config)) = &parsed {
+            problems.extend(config.problems());
+        }
+
+        let mut located: Vec<String> = problems
+            .into_iter()
+            .map(|(key, message)| {
+                let (line, column) = source.locate_key(&content, &key);
+                at((line, column, message))
+            })
+            .collect();
+
+        match parsed {
+            Ok(_) if located.is_empty() => Ok(Some(value)),
+            Ok(_) => Err(BumperError::ConfigError(located.join("\n  "))),
+            Err(e) => {
+                located.push(at(e));
+                Err(BumperError::ConfigError(located.join("\n  ")))
+            }
+        }
     }
 
-    pub fn load_from_path<P: AsRef<Path>>(path: P) -> Self {
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(user_config) = serde_json::from_str::<Config>(&content) {
-                return user_config;
-            } else {
-                eprintln!("Warning: Invalid .versionrc.json file, using default config");
+    /// Check values that parse but aren't meaningful, e.g. after CLI overrides
+    pub fn validate(&self) -> BumperResult<()> {
+        let problems: Vec<String> = self
+            .problems()
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(BumperError::ConfigError(problems.join("\n  ")))
+        }
+    }
+
+    /// `(key, message)` for every invalid preset or bump level
+    fn problems(&self) -> Vec<(String, String)> {
+        let mut problems = Vec::new();
+
+        if !PRESETS.contains(&self.preset.as_str()) {
+            problems.push((
+                "preset".to_string(),
+                invalid_value("preset", &self.preset, &PRESETS),
+            ));
+        }
+
+        let mut types: Vec<_> = self.types.iter().collect();
+        types.sort();
+        for (commit_type, bump) in types {
+            if !BUMP_LEVELS.contains(&bump.as_str()) {
+                problems.push((
+                    format!("types.{}", commit_type),
+                    invalid_value(&format!("types.{}", commit_type), bump, &BUMP_LEVELS),
+                ));
+            }
+        }
+
+        let placeholders = Regex::new(r"\{([^{}]*)\}").unwrap();
+        for captures in placeholders.captures_iter(&self.tag_message) {
+            if !TAG_PLACEHOLDERS.contains(&&captures[1]) {
+                let expected: Vec<String> = TAG_PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!("{{{}}}", p))
+                    .collect();
+                problems.push((
+                    "tagMessage".to_string(),
+                    format!(
+                        "`tagMessage` has unknown placeholder {}, expected one of {}",
+                        &captures[0],
+                        expected.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        for (index, rule) in self.branches.rules.iter().enumerate() {
+            let key = format!("branches.rules.{}", index);
+            if let Some(channel) = &rule.prerelease {
+                let valid = !channel.is_empty()
+                    && channel
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    && channel.parse::<u64>().is_err();
+                if !valid {
+                    problems.push((
+                        format!("{}.prerelease", key),
+                        format!(
+                            "`{}.prerelease` must be letters, digits and hyphens, got \"{}\"",
+                            key, channel
+                        ),
+                    ));
+                }
             }
+            if let Some(Err(message)) = rule.range.as_deref().map(str::parse::<VersionRange>) {
+                problems.push((
+                    format!("{}.range", key),
+                    format!("`{}.range`: {}", key, message),
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Collect keys of `value` that don't exist in `known`, the serialised defaults
+fn unknown_keys(
+    value: &serde_json::Value,
+    known: &serde_json::Value,
+    prefix: &str,
+    problems: &mut Vec<(String, String)>,
+) {
+    let (Some(object), Some(known_object)) = (value.as_object(), known.as_object()) else {
+        return;
+    };
+
+    for (key, child) in object {
+        let path = format!("{}{}", prefix, key);
+        match known_object.get(key) {
+            Some(known_child) => {
+                if !OPEN_OBJECTS.contains(&path.as_str()) {
+                    unknown_keys(child, known_child, &format!("{}.", path), problems);
+                }
+            }
+            None => {
+                let candidates: Vec<&str> = known_object.keys().map(String::as_str).collect();
+                let mut message = format!("unknown key `{}`", path);
+                if let Some(suggestion) = suggest(key, &candidates) {
+                    message.push_str(&format!(" (did you mean `{}{}`?)", prefix, suggestion));
+                }
+                problems.push((path, message));
+            }
+        }
+    }
+}
+
+fn invalid_value(key: &str, value: &str, allowed: &[&str]) -> String {
+    let mut message = format!(
+        "`{}` has invalid value \"{}\" (expected {})",
+        key,
+        value,
+        allowed.join(", ")
+    );
+    if let Some(suggestion) = suggest(value, allowed) {
+        message.push_str(&format!("; did you mean \"{}\"?", suggestion));
+    }
+    message
+}
+
+/// Closest candidate within a couple of typos
+fn suggest<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let threshold = (input.len() / 3).max(2);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(input, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_strip_comments_keeps_strings_and_lines() {
+        let content = "// header\n{\n  \"url\": \"https://example.com\", /* inline */\n  /* multi\n  line */ \"tag\": true // trailing\n}\n";
+
+        let stripped = strip_comments(content);
+
+        assert_eq!(stripped.lines().count(), content.lines().count());
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["url"], "https://example.com");
+        assert_eq!(value["tag"], true);
+    }
+
+    #[test]
+    fn test_strip_comments_ignores_escaped_quotes() {
+        let stripped = strip_comments(r#"{"prefix": "say \"// hi\""}"#);
+
+        assert_eq!(stripped, r#"{"prefix": "say \"// hi\""}"#);
+    }
+
+    fn load_file(name: &str, content: &str) -> BumperResult<Config> {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(name);
+        fs::write(&path, content).unwrap();
+        let mut layers = ConfigLayers::new();
+        layers.add_file(&ConfigSource::for_path(&path), Origin::Repo(path.clone()))?;
+        layers.config()
+    }
+
+    fn load(content: &str) -> BumperResult<Config> {
+        load_file(CONFIG_FILE, content)
+    }
+
+    fn error_message(content: &str) -> String {
+        file_error_message(CONFIG_FILE, content)
+    }
+
+    fn file_error_message(name: &str, content: &str) -> String {
+        match load_file(name, content) {
+            Err(BumperError::ConfigError(message)) => message,
+            other => panic!("expected a config error, got {:?}", other),
         }
+    }
+
+    #[test]
+    fn test_load_explicit_missing_file_fails() {
+        let result = ConfigLayers::load(Some(Path::new("/nonexistent/.versionrc.json")));
+
+        assert!(matches!(result, Err(BumperError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_load_toml_and_yaml() {
+        let toml = load_file(
+            ".versionrc.toml",
+            "tagPrefix = \"release-\"\n\n[types]\nconfig = \"patch\"\n",
+        )
+        .unwrap();
+        let yaml = load_file(
+            ".versionrc.yaml",
+            "tagPrefix: release-\ntypes:\n  config: patch\n",
+        )
+        .unwrap();
+
+        for config in [toml, yaml] {
+            assert_eq!(config.tag_prefix, "release-");
+            assert_eq!(
+                config.types.get("config").map(String::as_str),
+                Some("patch")
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_manifest_sections() {
+        let cargo = load_file(
+            "Cargo.toml",
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\n\n[package.metadata.grubble]\npreset = \"rust\"\n",
+        )
+        .unwrap();
+        let package = load_file(
+            "package.json",
+            r#"{"version": "1.0.0", "grubble": {"tag": true}}"#,
+        )
+        .unwrap();
+        let without_section = load_file("package.json", r#"{"version": "1.0.0"}"#).unwrap();
+
+        assert_eq!(cargo.preset, "rust");
+        assert!(package.tag);
+        assert!(!without_section.tag);
+    }
+
+    #[test]
+    fn test_errors_in_other_formats_report_position() {
+        let cargo = file_error_message(
+            "Cargo.toml",
+            "[package]\nversion = \"1.0.0\"\n\n[package.metadata.grubble]\nprest = \"rust\"\n",
+        );
+        let yaml = file_error_message(".versionrc.yaml", "tag: true\npush: maybe\n");
+
+        assert!(
+            cargo.ends_with("Cargo.toml:5:1: unknown key `prest` (did you mean `preset`?)"),
+            "{}",
+            cargo
+        );
+        assert!(yaml.contains(".versionrc.yaml:2:"), "{}", yaml);
+    }
+
+    #[test]
+    fn test_load_valid_config() {
+        let config =
+            load("{\n  \"tagPrefix\": \"release-\",\n  \"types\": { \"config\": \"patch\" }\n}")
+                .unwrap();
+
+        assert_eq!(config.tag_prefix, "release-");
+        assert_eq!(
+            config.types.get("config").map(String::as_str),
+            Some("patch")
+        );
+    }
+
+    #[test]
+    fn test_unknown_key_suggests_closest() {
+        let message = error_message("{\n  \"tag\": true,\n  \"tagPrefx\": \"v\"\n}");
+
+        assert!(message.ends_with(":3:3: unknown key `tagPrefx` (did you mean `tagPrefix`?)"));
+    }
+
+    #[test]
+    fn test_unknown_nested_key() {
+        let message = error_message(r#"{"releaseDate": {"sorce": "commit"}}"#);
+
+        assert!(message
+            .contains("unknown key `releaseDate.sorce` (did you mean `releaseDate.source`?)"));
+    }
+
+    #[test]
+    fn test_invalid_bump_and_preset_values() {
+        let message =
+            error_message("{\n  \"preset\": \"rsut\",\n  \"types\": {\"feat\": \"minr\"}\n}");
+
+        assert!(message.contains(":2:3: `preset` has invalid value \"rsut\""));
+        assert!(message.contains("did you mean \"rust\"?"));
+        assert!(message.contains(":3:13: `types.feat` has invalid value \"minr\""));
+        assert!(message.contains("did you mean \"minor\"?"));
+    }
+
+    #[test]
+    fn test_unknown_tag_message_placeholder() {
+        let message = error_message("{\n  \"tagMessage\": \"{tag} on {today}\"\n}");
+
+        assert!(message.contains(":2:3: `tagMessage` has unknown placeholder {today}"));
+        assert!(load("{\"tagMessage\": \"{tag}: {summary}\"}").is_ok());
+    }
+
+    #[test]
+    fn test_syntax_and_type_errors_report_position() {
+        let syntax = error_message("{\n  \"tag\": true,\n}");
+        let wrong_type = error_message("{\n  \"push\": \"yes\"\n}");
 
-        Config::default()
+        assert!(syntax.contains(":3:1: trailing comma"), "{}", syntax);
+        assert!(wrong_type.contains(":2:"), "{}", wrong_type);
+        assert!(wrong_type.contains("expected a boolean"), "{}", wrong_type);
     }
 }
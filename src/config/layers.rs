@@ -0,0 +1,573 @@
+use super::{preset_package_files, suggest, Config, ConfigSource, OPEN_OBJECTS};
+use crate::error::{BumperError, BumperResult};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Prefix of environment variables that override config keys
+pub const ENV_PREFIX: &str = "GRUBBLE_";
+
+/// Where an effective config value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    /// `packageFiles` derived from the preset
+    Preset(String),
+    User(PathBuf),
+    Repo(PathBuf),
+    Env(String),
+    Cli(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::Preset(preset) => write!(f, "default for preset {}", preset),
+            Origin::User(path) => write!(f, "user config {}", path.display()),
+            Origin::Repo(path) => write!(f, "repo config {}", path.display()),
+            Origin::Env(name) => write!(f, "env {}", name),
+            Origin::Cli(flag) => write!(f, "flag {}", flag),
+        }
+    }
+}
+
+/// A single value set from the command line, as text to be interpreted
+/// as the type of its key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    /// Dotted key path, e.g. `tagPrefix` or `types.config`
+    pub key: String,
+    pub text: String,
+    pub origin: Origin,
+}
+
+impl Override {
+    pub fn new(key: &str, text: &str, flag: &str) -> Self {
+        Override {
+            key: key.to_string(),
+            text: text.to_string(),
+            origin: Origin::Cli(flag.to_string()),
+        }
+    }
+}
+
+/// The config merged from every layer, lowest precedence first: built-in
+/// defaults, user config, repo config, `GRUBBLE_*` environment variables
+/// and command-line flags. Each value remembers which layer set it.
+#[derive(Debug, Clone)]
+pub struct ConfigLayers {
+    value: Value,
+    origins: BTreeMap<String, Origin>,
+}
+
+impl ConfigLayers {
+    /// Only the built-in defaults
+    pub fn new() -> Self {
+        let value = serde_json::to_value(Config::default()).expect("defaults serialise");
+        let mut leaves = Vec::new();
+        collect_leaves(&value, "", &mut leaves);
+        let origins = leaves
+            .into_iter()
+            .map(|(key, _)| (key, Origin::Default))
+            .collect();
+
+        ConfigLayers { value, origins }
+    }
+
+    /// Defaults, the user config, the repo config (`explicit`, or the first
+    /// one found in the current directory) and the environment
+    pub fn load(explicit: Option<&Path>) -> BumperResult<Self> {
+        let mut layers = Self::new();
+
+        if let Some(path) = user_config_path() {
+            layers.add_file(&ConfigSource::for_path(&path), Origin::User(path.clone()))?;
+        }
+
+        match explicit {
+            Some(path) if !path.exists() => {
+                return Err(BumperError::ConfigError(format!(
+                    "{}: file not found",
+                    path.display()
+                )));
+            }
+            Some(path) => {
+                layers.add_file(&ConfigSource::for_path(path), Origin::Repo(path.into()))?;
+            }
+            None => {
+                let sources = ConfigSource::discover(Path::new(""));
+                if let Some((source, ignored)) = sources.split_first() {
+                    for other in ignored {
+                        eprintln!(
+                            "Warning: Ignoring config in {} because {} takes precedence",
+                            other.path.display(),
+                            source.path.display()
+                        );
+                    }
+                    layers.add_file(source, Origin::Repo(source.path.clone()))?;
+                }
+            }
+        }
+
+        layers.add_env(std::env::vars())?;
+        Ok(layers)
+    }
+
+    /// Merge a validated config file over the current values. Open objects
+    /// such as `types` replace the ones from earlier layers as a whole, as
+    /// they list every entry.
+    pub fn add_file(&mut self, source: &ConfigSource, origin: Origin) -> BumperResult<()> {
+        let Some(value) = Config::read_file(source)? else {
+            return Ok(());
+        };
+
+        for open in OPEN_OBJECTS {
+            if value.get(open).is_some() {
+                self.clear(open);
+            }
+        }
+        let mut leaves = Vec::new();
+        collect_leaves(&value, "", &mut leaves);
+        for (key, value) in leaves {
+            self.set(&key, value.clone(), origin.clone());
+        }
+        Ok(())
+    }
+
+    /// Apply `GRUBBLE_*` variables, e.g. `GRUBBLE_TAG_PREFIX` for `tagPrefix`
+    /// or `GRUBBLE_RELEASE_DATE__SOURCE` for `releaseDate.source`
+    pub fn add_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> BumperResult<()> {
+        let names: BTreeMap<String, String> = self
+            .origins
+            .keys()
+            .filter(|key| !is_open_entry(key))
+            .map(|key| (env_name(key), key.clone()))
+            .collect();
+
+        let mut vars: Vec<_> = vars
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+            .collect();
+        vars.sort();
+
+        for (name, text) in vars {
+            let Some(key) = env_key(&name, &names) else {
+                let candidates: Vec<&str> = names.keys().map(String::as_str).collect();
+                let mut warning =
+                    format!("Warning: Ignoring unknown environment variable {}", name);
+                if let Some(suggestion) = suggest(&name, &candidates) {
+                    warning.push_str(&format!(" (did you mean {}?)", suggestion));
+                }
+                eprintln!("{}", warning);
+                continue;
+            };
+
+            let value = self
+                .parse(&key, &text)
+                .map_err(|message| BumperError::ConfigError(format!("{}: {}", name, message)))?;
+            self.set(&key, value, Origin::Env(name));
+        }
+        Ok(())
+    }
+
+    pub fn add_overrides(&mut self, overrides: Vec<Override>) -> BumperResult<()> {
+        for Override { key, text, origin } in overrides {
+            let value = self
+                .check_key(&key)
+                .and_then(|_| self.parse(&key, &text))
+                .map_err(|message| BumperError::ConfigError(format!("{}: {}", origin, message)))?;
+            self.set(&key, value, origin);
+        }
+        Ok(())
+    }
+
+    /// Whether `key` is a single value that can be overridden
+    fn check_key(&self, key: &str) -> Result<(), String> {
+        if self.origins.contains_key(key) || is_open_entry(key) {
+            return Ok(());
+        }
+
+        let prefix = format!("{}.", key);
+        if let Some(example) = self.origins.keys().find(|k| k.starts_with(&prefix)) {
+            return Err(format!(
+                "`{}` is a section, set one of its keys such as `{}`",
+                key, example
+            ));
+        }
+
+        let candidates: Vec<&str> = self.origins.keys().map(String::as_str).collect();
+        let mut message = format!("unknown key `{}`", key);
+        if let Some(suggestion) = suggest(key, &candidates) {
+            message.push_str(&format!(" (did you mean `{}`?)", suggestion));
+        }
+        Err(message)
+    }
+
+    /// Interpret text from the environment or command line as the type of `key`
+    fn parse(&self, key: &str, text: &str) -> Result<Value, String> {
+        match self.get(key) {
+            Some(Value::Bool(_)) => match text.to_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Ok(Value::Bool(true)),
+                "false" | "0" | "no" | "off" | "" => Ok(Value::Bool(false)),
+                _ => Err(format!("expected true or false, got \"{}\"", text)),
+            },
+            Some(Value::Array(_)) => Ok(Value::Array(
+                text.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_string()))
+                    .collect(),
+            )),
+            _ => Ok(Value::String(text.to_string())),
+        }
+    }
+
+    /// The effective config, validated
+    pub fn config(&self) -> BumperResult<Config> {
+        let config: Config = serde_json::from_value(self.resolved().value)
+            .map_err(|e| BumperError::ConfigError(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Every effective value with the layer that set it, in key order
+    pub fn entries(&self) -> Vec<(String, Value, Origin)> {
+        let resolved = self.resolved();
+        resolved
+            .origins
+            .iter()
+            .map(|(key, origin)| {
+                let value = resolved.get(key).cloned().unwrap_or(Value::Null);
+                (key.clone(), value, origin.clone())
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let resolved = self.resolved();
+        let sources: Map<String, Value> = resolved
+            .origins
+            .iter()
+            .map(|(key, origin)| (key.clone(), Value::String(origin.to_string())))
+            .collect();
+
+        serde_json::json!({ "config": resolved.value, "sources": sources })
+    }
+
+    /// Fill in defaults that depend on other values
+    fn resolved(&self) -> Self {
+        let mut resolved = self.clone();
+        if resolved.origins.get("packageFiles") == Some(&Origin::Default) {
+            let preset = resolved
+                .get("preset")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            resolved.set(
+                "packageFiles",
+                serde_json::json!(preset_package_files(&preset)),
+                Origin::Preset(preset),
+            );
+        }
+        resolved
+    }
+
+    /// Remove every entry of the open object `key`
+    fn clear(&mut self, key: &str) {
+        if let Some(object) = self.value.get_mut(key).and_then(Value::as_object_mut) {
+            object.clear();
+        }
+        let prefix = format!("{}.", key);
+        self.origins.retain(|entry, _| !entry.starts_with(&prefix));
+    }
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        key.split('.')
+            .try_fold(&self.value, |value, segment| value.get(segment))
+    }
+
+    fn set(&mut self, key: &str, value: Value, origin: Origin) {
+        let mut target = &mut self.value;
+        for segment in key.split('.') {
+            if !target.is_object() {
+                *target = Value::Object(Map::new());
+            }
+            target = target
+                .as_object_mut()
+                .unwrap()
+                .entry(segment)
+                .or_insert(Value::Null);
+        }
+        *target = value;
+        self.origins.insert(key.to_string(), origin);
+    }
+}
+
+/// The user config in the XDG config directory, if one exists
+fn user_config_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+
+    ["config.json", "config.toml", "config.yaml", "config.yml"]
+        .iter()
+        .map(|name| dir.join("grubble").join(name))
+        .find(|path| path.exists())
+}
+
+/// Leaf values by dotted key path. Entries of open objects such as `types`
+/// are leaves, so the environment and flags can set them one by one.
+fn collect_leaves<'a>(value: &'a Value, prefix: &str, leaves: &mut Vec<(String, &'a Value)>) {
+    let Some(object) = value.as_object() else {
+        return;
+    };
+
+    for (key, child) in object {
+        let path = format!("{}{}", prefix, key);
+        if child.is_object() && !is_open_entry(&path) {
+            collect_leaves(child, &format!("{}.", path), leaves);
+        } else {
+            leaves.push((path, child));
+        }
+    }
+}
+
+fn is_open_entry(key: &str) -> bool {
+    OPEN_OBJECTS.iter().any(|open| {
+        key.strip_prefix(open)
+            .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// `releaseDate.source` -> `GRUBBLE_RELEASE_DATE__SOURCE`
+fn env_name(key: &str) -> String {
+    let segments: Vec<String> = key
+        .split('.')
+        .map(|segment| {
+            let mut name = String::new();
+            for c in segment.chars() {
+                if c.is_ascii_uppercase() {
+                    name.push('_');
+                }
+                name.push(c.to_ascii_uppercase());
+            }
+            name
+        })
+        .collect();
+    format!("{}{}", ENV_PREFIX, segments.join("__"))
+}
+
+/// The key an environment variable sets, including entries of open objects
+/// such as `GRUBBLE_TYPES__CONFIG`
+fn env_key(name: &str, names: &BTreeMap<String, String>) -> Option<String> {
+    if let Some(key) = names.get(name) {
+        return Some(key.clone());
+    }
+
+    OPEN_OBJECTS.iter().find_map(|open| {
+        let entry = name.strip_prefix(&format!("{}__", env_name(open)))?;
+        (!entry.is_empty()).then(|| format!("{}.{}", open, entry.to_lowercase()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_name() {
+        assert_eq!(env_name("tagPrefix"), "GRUBBLE_TAG_PREFIX");
+        assert_eq!(
+            env_name("releaseDate.source"),
+            "GRUBBLE_RELEASE_DATE__SOURCE"
+        );
+    }
+
+    #[test]
+    fn test_layers_apply_in_order_and_track_origins() {
+        let temp_dir = TempDir::new().unwrap();
+        let user = temp_dir.path().join("config.toml");
+        let repo = temp_dir.path().join(".versionrc.json");
+        std::fs::write(&user, "tagPrefix = \"user-\"\ngitUserName = \"ci-bot\"\n").unwrap();
+        std::fs::write(
+            &repo,
+            r#"{"tagPrefix": "repo-", "types": {"config": "patch"}}"#,
+        )
+        .unwrap();
+
+        let mut layers = ConfigLayers::new();
+        layers
+            .add_file(&ConfigSource::for_path(&user), Origin::User(user.clone()))
+            .unwrap();
+        layers
+            .add_file(&ConfigSource::for_path(&repo), Origin::Repo(repo.clone()))
+            .unwrap();
+        layers
+            .add_env(vars(&[
+                ("GRUBBLE_TAG_PREFIX", "env-"),
+                ("GRUBBLE_PUSH", "yes"),
+                ("HOME", "/root"),
+            ]))
+            .unwrap();
+        layers
+            .add_overrides(vec![Override::new("push", "false", "--no-push")])
+            .unwrap();
+
+        let config = layers.config().unwrap();
+        let origins: BTreeMap<String, Origin> = layers
+            .entries()
+            .into_iter()
+            .map(|(key, _, origin)| (key, origin))
+            .collect();
+
+        assert_eq!(config.tag_prefix, "env-");
+        assert_eq!(config.git_user_name, "ci-bot");
+        assert!(!config.push);
+        assert_eq!(
+            config.types.get("config").map(String::as_str),
+            Some("patch")
+        );
+        // The repo's types replace the defaults
+        assert_eq!(config.types.len(), 1);
+        assert_eq!(
+            origins["tagPrefix"],
+            Origin::Env("GRUBBLE_TAG_PREFIX".to_string())
+        );
+        assert_eq!(origins["gitUserName"], Origin::User(user));
+        assert_eq!(origins["types.config"], Origin::Repo(repo));
+        assert_eq!(origins["push"], Origin::Cli("--no-push".to_string()));
+        assert_eq!(origins["commitPrefix"], Origin::Default);
+    }
+
+    #[test]
+    fn test_file_types_replace_earlier_layers_and_env_extends_them() {
+        let temp_dir = TempDir::new().unwrap();
+        let user = temp_dir.path().join("config.toml");
+        let repo = temp_dir.path().join(".versionrc.json");
+        std::fs::write(&user, "[types]\nconfig = \"patch\"\n").unwrap();
+        std::fs::write(&repo, r#"{"types": {"feat": "minor", "fix": "patch"}}"#).unwrap();
+
+        let mut layers = ConfigLayers::new();
+        layers
+            .add_file(&ConfigSource::for_path(&user), Origin::User(user))
+            .unwrap();
+        layers
+            .add_file(&ConfigSource::for_path(&repo), Origin::Repo(repo.clone()))
+            .unwrap();
+        layers
+            .add_env(vars(&[("GRUBBLE_TYPES__DOCS", "patch")]))
+            .unwrap();
+        layers
+            .add_overrides(vec![Override::new("types.perf", "patch", "--type")])
+            .unwrap();
+
+        let config = layers.config().unwrap();
+        let mut types: Vec<(&str, &str)> = config
+            .types
+            .iter()
+            .map(|(commit_type, bump)| (commit_type.as_str(), bump.as_str()))
+            .collect();
+        types.sort();
+        assert_eq!(
+            types,
+            vec![
+                ("docs", "patch"),
+                ("feat", "minor"),
+                ("fix", "patch"),
+                ("perf", "patch")
+            ]
+        );
+        let origins: Vec<(String, Origin)> = layers
+            .entries()
+            .into_iter()
+            .filter(|(key, _, _)| key.starts_with("types."))
+            .map(|(key, _, origin)| (key, origin))
+            .collect();
+        assert_eq!(origins.len(), 4);
+        assert_eq!(origins[1], ("types.feat".to_string(), Origin::Repo(repo)));
+    }
+
+    #[test]
+    fn test_env_parses_by_type() {
+        let mut layers = ConfigLayers::new();
+        layers
+            .add_env(vars(&[
+                ("GRUBBLE_CHANGELOG_FILES", "CHANGELOG.md, docs/CHANGES.md"),
+                ("GRUBBLE_TYPES__CONFIG", "patch"),
+                ("GRUBBLE_RELEASE_DATE__SOURCE", "commit"),
+            ]))
+            .unwrap();
+        let config = layers.config().unwrap();
+
+        assert_eq!(
+            config.changelog_files,
+            vec!["CHANGELOG.md", "docs/CHANGES.md"]
+        );
+        assert_eq!(
+            config.types.get("config").map(String::as_str),
+            Some("patch")
+        );
+        assert_eq!(
+            config.release_date.source,
+            crate::config::DateSource::Commit
+        );
+
+        let error = ConfigLayers::new()
+            .add_env(vars(&[("GRUBBLE_TAG", "maybe")]))
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("GRUBBLE_TAG: expected true or false"));
+    }
+
+    #[test]
+    fn test_overrides_check_keys() {
+        let error = |key: &str| {
+            ConfigLayers::new()
+                .add_overrides(vec![Override::new(key, "x", &format!("--set {}", key))])
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(error("tagPrefx")
+            .contains("flag --set tagPrefx: unknown key `tagPrefx` (did you mean `tagPrefix`?)"));
+        assert!(error("releaseDate").contains("`releaseDate` is a section"));
+        assert!(ConfigLayers::new()
+            .add_overrides(vec![Override::new(
+                "types.config",
+                "patch",
+                "--type config"
+            )])
+            .is_ok());
+    }
+
+    #[test]
+    fn test_package_files_default_from_preset() {
+        let mut layers = ConfigLayers::new();
+        layers
+            .add_overrides(vec![Override::new("preset", "rust", "--preset")])
+            .unwrap();
+
+        let entries = layers.entries();
+        let (_, value, origin) = entries
+            .iter()
+            .find(|(key, ..)| key == "packageFiles")
+            .unwrap();
+
+        assert_eq!(value, &serde_json::json!(["Cargo.toml"]));
+        assert_eq!(origin, &Origin::Preset("rust".to_string()));
+    }
+}
@@ -0,0 +1,267 @@
+use super::{strip_comments, Config};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Config locations searched when `--config` isn't given, highest
+/// precedence first. Only the first one found is used.
+pub const SEARCH_ORDER: [&str; 7] = [
+    ".versionrc.json",
+    ".versionrc",
+    ".versionrc.toml",
+    ".versionrc.yaml",
+    ".versionrc.yml",
+    "Cargo.toml",
+    "package.json",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+/// A file holding grubble's config, either on its own or in a section of a
+/// package manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    pub format: Format,
+    /// Keys leading to the config inside a manifest, e.g. `package.metadata.grubble`
+    pub section: &'static [&'static str],
+}
+
+/// A parse error at a 1-based line and column
+pub type Located = (usize, usize, String);
+
+#[derive(Deserialize)]
+struct CargoManifest {
+    #[serde(default)]
+    package: Option<CargoPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    #[serde(default)]
+    metadata: Option<CargoMetadata>,
+}
+
+#[derive(Deserialize)]
+struct CargoMetadata {
+    #[serde(default)]
+    grubble: Option<Config>,
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    #[serde(default)]
+    grubble: Option<Config>,
+}
+
+impl ConfigSource {
+    /// The source for an explicit path, its format taken from the file name
+    pub fn for_path(path: &Path) -> Self {
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let (format, section): (Format, &'static [&'static str]) = match file_name {
+            "Cargo.toml" => (Format::Toml, &["package", "metadata", "grubble"]),
+            "package.json" => (Format::Json, &["grubble"]),
+            _ => match extension {
+                "toml" => (Format::Toml, &[]),
+                "yaml" | "yml" => (Format::Yaml, &[]),
+                _ => (Format::Json, &[]),
+            },
+        };
+
+        ConfigSource {
+            path: path.to_path_buf(),
+            format,
+            section,
+        }
+    }
+
+    /// Every source in `dir` that holds a config, in precedence order.
+    /// Manifests only count when they have a grubble section.
+    pub fn discover(dir: &Path) -> Vec<Self> {
+        SEARCH_ORDER
+            .iter()
+            .map(|name| Self::for_path(&dir.join(name)))
+            .filter(|source| {
+                if source.section.is_empty() {
+                    return source.path.exists();
+                }
+                std::fs::read_to_string(&source.path)
+                    .ok()
+                    .and_then(|content| source.parse_value(&content).ok())
+                    .flatten()
+                    .is_some()
+            })
+            .collect()
+    }
+
+    /// Text the parsers see; JSON files may contain comments
+    pub fn prepare(&self, content: &str) -> String {
+        match self.format {
+            Format::Json => strip_comments(content),
+            Format::Toml | Format::Yaml => content.to_string(),
+        }
+    }
+
+    /// The config section as untyped JSON, `None` if a manifest has no section
+    pub fn parse_value(&self, content: &str) -> Result<Option<serde_json::Value>, Located> {
+        let mut value: serde_json::Value = match self.format {
+            Format::Json => serde_json::from_str(content).map_err(|e| json_error(&e))?,
+            Format::Toml => toml::from_str(content).map_err(|e| toml_error(content, &e))?,
+            Format::Yaml => serde_yaml::from_str(content).map_err(|e| yaml_error(&e))?,
+        };
+
+        for key in self.section {
+            match value.get_mut(*key) {
+                Some(inner) => value = inner.take(),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(value))
+    }
+
+    /// The typed config, parsed from the text so errors keep their position
+    pub fn parse_config(&self, content: &str) -> Result<Option<Config>, Located> {
+        match (self.format, self.section.is_empty()) {
+            (Format::Json, true) => serde_json::from_str(content)
+                .map(Some)
+                .map_err(|e| json_error(&e)),
+            (Format::Toml, true) => toml::from_str(content)
+                .map(Some)
+                .map_err(|e| toml_error(content, &e)),
+            (Format::Yaml, _) => serde_yaml::from_str(content)
+                .map(Some)
+                .map_err(|e| yaml_error(&e)),
+            (Format::Toml, false) => toml::from_str::<CargoManifest>(content)
+                .map(|manifest| manifest.package?.metadata?.grubble)
+                .map_err(|e| toml_error(content, &e)),
+            (Format::Json, false) => serde_json::from_str::<PackageJson>(content)
+                .map(|package| package.grubble)
+                .map_err(|e| json_error(&e)),
+        }
+    }
+
+    /// Line and column of a dotted key path within the config section, found
+    /// by searching for each segment after the previous one
+    pub fn locate_key(&self, content: &str, key: &str) -> (usize, usize) {
+        let mut offset = 0;
+        for segment in self.section.iter().copied().chain(key.split('.')) {
+            let escaped = regex::escape(segment);
+            let pattern = match self.format {
+                Format::Json => format!(r#""{}"\s*:"#, escaped),
+                // Also matches table headers such as `[package.metadata.grubble]`
+                Format::Toml => format!(r#"\b{}\b"?\s*[=.\]]"#, escaped),
+                Format::Yaml => format!(r#"(?m)^[ \t]*["']?{}["']?\s*:"#, escaped),
+            };
+            if let Some(found) = Regex::new(&pattern).unwrap().find(&content[offset..]) {
+                offset += found.start();
+            }
+        }
+
+        let offset = offset + content[offset..].len() - content[offset..].trim_start().len();
+        line_column(content, offset)
+    }
+}
+
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+/// serde_json appends the position to its messages; it is reported separately
+fn json_error(error: &serde_json::Error) -> Located {
+    let message = error.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    };
+    (error.line(), error.column(), message)
+}
+
+fn toml_error(content: &str, error: &toml::de::Error) -> Located {
+    let (line, column) = error
+        .span()
+        .map_or((1, 1), |span| line_column(content, span.start));
+    (line, column, error.message().to_string())
+}
+
+fn yaml_error(error: &serde_yaml::Error) -> Located {
+    let (line, column) = error
+        .location()
+        .map_or((1, 1), |location| (location.line(), location.column()));
+    let message = error.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    };
+    (line, column, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_for_path_detects_format() {
+        let cargo = ConfigSource::for_path(Path::new("crates/app/Cargo.toml"));
+        assert_eq!(cargo.format, Format::Toml);
+        assert_eq!(cargo.section, ["package", "metadata", "grubble"]);
+
+        assert_eq!(
+            ConfigSource::for_path(Path::new("ci/release.yml")).format,
+            Format::Yaml
+        );
+        assert_eq!(
+            ConfigSource::for_path(Path::new(".versionrc")).format,
+            Format::Json
+        );
+    }
+
+    #[test]
+    fn test_discover_skips_manifests_without_section() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"version": "1.0.0"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[package.metadata.grubble]\ntagPrefix = \"v\"\n",
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join(".versionrc.yaml"), "tag: true\n").unwrap();
+
+        let found: Vec<PathBuf> = ConfigSource::discover(temp_dir.path())
+            .into_iter()
+            .map(|source| source.path)
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                temp_dir.path().join(".versionrc.yaml"),
+                temp_dir.path().join("Cargo.toml")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locate_key_in_cargo_metadata() {
+        let source = ConfigSource::for_path(Path::new("Cargo.toml"));
+        let content = "[package]\nversion = \"1.0.0\"\n\n[package.metadata.grubble]\npreset = \"rust\"\nversion = 1\n";
+
+        assert_eq!(source.locate_key(content, "version"), (6, 1));
+    }
+}
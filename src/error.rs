@@ -12,6 +12,41 @@ pub enum BumperError {
     #[error("Invalid version format: {0}")]
     InvalidVersion(String),
 
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+
+    #[error("Changelog already contains an entry for {0} in {1}")]
+    ChangelogEntryExists(String, String),
+
+    #[error("Cannot release {0}: it must be greater than the current version {1}")]
+    VersionNotGreater(String, String),
+
+    #[error("Branch '{0}' may not release: no rule in branches.rules matches it")]
+    BranchNotReleasable(String),
+
+    #[error("Cannot release {0} from branch '{1}': it is outside the branch's range {2}")]
+    VersionOutOfRange(String, String, String),
+
+    #[error("Signing is enabled but git can't sign: {0}")]
+    SigningNotConfigured(String),
+
+    #[error("Shallow clone without a release tag in reach, so the version would be worked out from a truncated history. Fetch the full history (e.g. fetch-depth: 0 for actions/checkout), or set shallowClone to \"deepen\" to let grubble fetch it")]
+    ShallowClone,
+
+    #[error("Pre-flight checks failed, nothing was changed:\n  - {}", .0.join("\n  - "))]
+    PreflightFailed(Vec<String>),
+
+    #[error("{0}\nThe release was rolled back: local commits, tags and files are as they were before the run")]
+    RolledBack(Box<BumperError>),
+
+    #[error(
+        "{0}\nRolling back the release also failed, the repository may be partly released: {1}"
+    )]
+    RollbackFailed(Box<BumperError>, String),
+
+    #[error("Verification failed with {0} problem(s)")]
+    VerificationFailed(usize),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -1,15 +1,23 @@
-use crate::config::Config;
+use crate::config::{Config, SigningConfig, SigningFormat};
 use crate::error::{BumperError, BumperResult};
-use crate::versioner::Version;
+use crate::plan::ReleasePlan;
+use crate::versioner::{Version, VersionRange};
+use std::collections::BTreeMap;
 use std::process::Command;
 
-fn run_git_command(args: &[&str]) -> BumperResult<String> {
-    let output = Command::new("git")
+pub fn run_git_command(args: &[&str]) -> BumperResult<String> {
+    let mut command = Command::new("git");
+    command
         .args(args)
         .env("GIT_TERMINAL_PROMPT", "0")
         .env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes")
         .env("GIT_ASKPASS", "")
-        .env("SSH_ASKPASS", "")
+        .env("SSH_ASKPASS", "");
+    #[cfg(test)]
+    if let Some(dir) = test_repo::current() {
+        command.current_dir(dir);
+    }
+    let output = command
         .output()
         .map_err(|e| BumperError::GitError(format!("Failed to execute git: {}", e)))?;
 
@@ -29,16 +37,169 @@ fn run_git_command(args: &[&str]) -> BumperResult<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-pub fn get_last_tag() -> BumperResult<Option<String>> {
-    match run_git_command(&["describe", "--tags", "--abbrev=0"]) {
-        Ok(tag) if !tag.is_empty() => Ok(Some(tag)),
-        Ok(_) => Ok(None),
-        Err(_) => Ok(None), // No tags exist yet
+pub fn is_repository() -> bool {
+    run_git_command(&["rev-parse", "--git-dir"]).is_ok()
+}
+
+/// Whether this is a shallow clone, missing older history
+pub fn is_shallow() -> bool {
+    run_git_command(&["rev-parse", "--is-shallow-repository"]).is_ok_and(|output| output == "true")
+}
+
+pub fn fetch_tags(remote: &str) -> BumperResult<()> {
+    run_git_command(&["fetch", "--quiet", "--tags", remote]).map(|_| ())
+}
+
+/// Fetch `depth` more commits of history
+pub fn deepen(remote: &str, depth: u32) -> BumperResult<()> {
+    run_git_command(&["fetch", "--quiet", &format!("--deepen={}", depth), remote]).map(|_| ())
+}
+
+pub fn unshallow(remote: &str) -> BumperResult<()> {
+    run_git_command(&["fetch", "--quiet", "--unshallow", remote]).map(|_| ())
+}
+
+/// Tracked files with uncommitted changes, staged or not
+pub fn changed_files() -> BumperResult<Vec<String>> {
+    let output = run_git_command(&["diff", "--name-only", "HEAD", "--"])?;
+    Ok(output.lines().map(str::to_string).collect())
+}
+
+pub fn is_tracked(path: &str) -> bool {
+    run_git_command(&["ls-files", "--error-unmatch", "--", path]).is_ok()
+}
+
+/// Whether `ancestor` is in the history of `rev`. False when `ancestor` is
+/// a commit that isn't available locally.
+pub fn is_ancestor(ancestor: &str, rev: &str) -> bool {
+    run_git_command(&["merge-base", "--is-ancestor", ancestor, rev]).is_ok()
+}
+
+/// What `refs` point to on `remote`; refs it doesn't have are left out
+pub fn remote_refs(remote: &str, refs: &[String]) -> BumperResult<BTreeMap<String, String>> {
+    let mut args = vec!["ls-remote", remote];
+    args.extend(refs.iter().map(String::as_str));
+    let output = run_git_command(&args)?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(target, name)| (name.to_string(), target.to_string()))
+        .collect())
+}
+
+/// CI variables holding the branch being built, for detached HEAD checkouts.
+/// Pull request builds are checked first so they aren't mistaken for a
+/// build of the target branch.
+const CI_BRANCH_VARIABLES: [&str; 8] = [
+    "GITHUB_HEAD_REF",
+    "GITHUB_REF_NAME",
+    "CI_COMMIT_BRANCH",
+    "BUILDKITE_BRANCH",
+    "CIRCLE_BRANCH",
+    "TRAVIS_BRANCH",
+    "BRANCH_NAME",
+    "GIT_BRANCH",
+];
+
+/// The checked-out branch, falling back to CI variables when HEAD is detached
+pub fn current_branch() -> BumperResult<Option<String>> {
+    if let Ok(branch) = run_git_command(&["symbolic-ref", "--short", "-q", "HEAD"]) {
+        if !branch.is_empty() {
+            return Ok(Some(branch));
+        }
     }
+    Ok(branch_from_env(|name| std::env::var(name).ok()))
+}
+
+fn branch_from_env(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    CI_BRANCH_VARIABLES.iter().find_map(|name| {
+        let value = var(name)?;
+        // Jenkins reports the remote-tracking name, e.g. `origin/main`
+        let branch = value.strip_prefix("origin/").unwrap_or(&value).trim();
+        (!branch.is_empty()).then(|| branch.to_string())
+    })
+}
+
+/// The highest release tag reachable from HEAD. Only tags made of
+/// `tag_prefix` and a version count, so movable tags such as `v4` and
+/// unrelated ones such as `deploy-prod` are ignored. Branches without a
+/// prerelease channel skip prerelease tags, so a release after merging a
+/// prerelease branch covers every commit since the previous release, and
+/// maintenance branches only look within their range.
+pub fn get_last_tag(config: &Config) -> BumperResult<Option<String>> {
+    // Fails before the first commit, when there are no tags either
+    let output = run_git_command(&["tag", "--merged", "HEAD"]).unwrap_or_default();
+    Ok(select_last_tag(
+        output.lines(),
+        &config.tag_prefix,
+        config.branch.range.as_ref(),
+        config.branch.prerelease.is_some(),
+    ))
+}
+
+/// The release tag on `config.remote` that `get_last_tag` would pick if all
+/// of its tags were reachable
+pub fn get_remote_last_tag(config: &Config) -> BumperResult<Option<String>> {
+    let output = run_git_command(&["ls-remote", "--tags", "--refs", &config.remote])?;
+    let tags = output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter_map(|(_, name)| name.strip_prefix("refs/tags/"));
+    Ok(select_last_tag(
+        tags,
+        &config.tag_prefix,
+        config.branch.range.as_ref(),
+        config.branch.prerelease.is_some(),
+    ))
+}
+
+/// The tag with the highest version by SemVer precedence
+fn select_last_tag<'a>(
+    tags: impl IntoIterator<Item = &'a str>,
+    tag_prefix: &str,
+    range: Option<&VersionRange>,
+    allow_prerelease: bool,
+) -> Option<String> {
+    tags.into_iter()
+        .filter_map(|tag| {
+            let version = Version::parse(tag.strip_prefix(tag_prefix)?).ok()?;
+            let wanted = range.is_none_or(|range| range.contains(&version))
+                && (allow_prerelease || !version.is_prerelease());
+            wanted.then_some((version, tag))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, tag)| tag.to_string())
+}
+
+/// Every local tag ref, e.g. `refs/tags/v1.2.0`, and the object it points to
+pub fn tag_refs() -> BumperResult<BTreeMap<String, String>> {
+    let output = run_git_command(&[
+        "for-each-ref",
+        "--format=%(refname) %(objectname)",
+        "refs/tags",
+    ])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, target)| (name.to_string(), target.to_string()))
+        .collect())
+}
+
+pub fn list_tags() -> BumperResult<Vec<String>> {
+    let output = run_git_command(&["tag", "--list"])?;
+    Ok(output.lines().map(|s| s.to_string()).collect())
+}
+
+/// Versions of all tags starting with `tag_prefix`, ignoring other tags
+pub fn tag_versions(tag_prefix: &str) -> BumperResult<Vec<Version>> {
+    Ok(list_tags()?
+        .iter()
+        .filter_map(|tag| Version::parse(tag.strip_prefix(tag_prefix)?).ok())
+        .collect())
 }
 
 pub fn get_last_tag_version(config: &Config) -> BumperResult<Option<Version>> {
-    let last_tag = get_last_tag()?;
+    let last_tag = get_last_tag(config)?;
 
     if let Some(tag) = last_tag {
         let prefix = &config.tag_prefix;
@@ -54,72 +215,257 @@ pub fn get_last_tag_version(config: &Config) -> BumperResult<Option<Version>> {
     }
 }
 
-pub fn get_commits_since_tag(last_tag: Option<&str>) -> BumperResult<Vec<String>> {
-    let output = if let Some(tag) = last_tag {
-        let range = format!("{}..HEAD", tag);
-        run_git_command(&["log", &range, "--pretty=%s"])?
-    } else {
-        run_git_command(&["log", "--pretty=%s"])?
-    };
+/// A commit in the release range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// Abbreviated commit hash
+    pub hash: String,
+    /// Full commit message
+    pub message: String,
+}
 
-    if output.is_empty() {
-        return Ok(vec![]);
+impl Commit {
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or("")
     }
 
-    Ok(output
-        .lines()
-        .map(|s| s.to_string())
-        .filter(|s| !s.is_empty())
-        .collect())
+    /// Everything after the subject line
+    pub fn body(&self) -> &str {
+        self.message
+            .split_once('\n')
+            .map(|(_, body)| body)
+            .unwrap_or("")
+    }
 }
 
-pub fn commit_changes(version: &str, files: &[String], commit_prefix: &str) -> BumperResult<()> {
-    if files.is_empty() {
-        return Ok(());
+impl From<&str> for Commit {
+    fn from(message: &str) -> Self {
+        Commit {
+            hash: String::new(),
+            message: message.to_string(),
+        }
     }
+}
 
-    let mut add_args = vec!["add"];
-    for file in files {
-        add_args.push(file);
+/// Subjects of `commits`, as used for changelogs and release notes
+pub fn subjects(commits: &[Commit]) -> Vec<String> {
+    commits.iter().map(|c| c.subject().to_string()).collect()
+}
+
+// Separators unlikely to appear in commit messages
+const FIELD_SEPARATOR: char = '\x1f';
+const RECORD_SEPARATOR: char = '\x1e';
+
+/// List commits since `last_tag`, newest first, optionally limited to
+/// commits touching `path`
+pub fn get_commits_since_tag(
+    last_tag: Option<&str>,
+    path: Option<&str>,
+) -> BumperResult<Vec<Commit>> {
+    let range = last_tag.map(|tag| format!("{}..HEAD", tag));
+
+    let mut args = vec!["log"];
+    if let Some(range) = &range {
+        args.push(range);
+    }
+    args.push("--pretty=format:%h%x1f%B%x1e");
+    if let Some(path) = path {
+        args.push("--");
+        args.push(path);
     }
-    run_git_command(&add_args)?;
+    let output = run_git_command(&args)?;
 
-    let commit_message = format!("{} to {}", commit_prefix, version);
-    run_git_command(&["commit", "-m", &commit_message])?;
+    Ok(parse_log(&output))
+}
+
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .split(RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let (hash, message) = record.trim_start().split_once(FIELD_SEPARATOR)?;
+            let message = message.trim();
+            if message.is_empty() {
+                return None;
+            }
+            Some(Commit {
+                hash: hash.to_string(),
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
 
-    Ok(())
+/// Committer date of `rev` as seconds since the Unix epoch
+pub fn get_commit_timestamp(rev: &str) -> BumperResult<i64> {
+    let output = run_git_command(&["log", "-1", "--format=%ct", rev])?;
+    output
+        .parse::<i64>()
+        .map_err(|_| BumperError::GitError(format!("Unexpected commit timestamp: {}", output)))
 }
 
-pub fn create_tag(version: &str, tag_prefix: &str, message: Option<&str>) -> BumperResult<()> {
+pub fn commit_changes(
+    plan: &mut ReleasePlan,
+    version: &str,
+    files: &[String],
+    commit_prefix: &str,
+    signing: &SigningConfig,
+) {
+    let message = format!("{} to {}", commit_prefix, version);
+    let mut args = vec!["commit".to_string()];
+    if signing.commits {
+        args.push(match &signing.key {
+            Some(key) => format!("--gpg-sign={}", key),
+            None => "-S".to_string(),
+        });
+    }
+    args.extend(["-m".to_string(), message]);
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    plan.commit(files, &signing_options(signing, signing.commits), &args);
+}
+
+/// Plan the release tag and return its name. Signed tags are annotated, with
+/// the tag name as the message when there are no release notes.
+pub fn create_tag(
+    plan: &mut ReleasePlan,
+    version: &str,
+    tag_prefix: &str,
+    message: Option<&str>,
+    signing: &SigningConfig,
+) -> String {
     let tag_name = format!("{}{}", tag_prefix, version);
 
-    if let Some(msg) = message {
-        run_git_command(&["tag", "-a", &tag_name, "-m", msg])?;
-    } else {
-        run_git_command(&["tag", &tag_name])?;
+    let mut args = vec!["tag".to_string()];
+    if signing.tags {
+        args.push(match &signing.key {
+            Some(key) => format!("--local-user={}", key),
+            None => "-s".to_string(),
+        });
+    } else if message.is_some() {
+        args.push("-a".to_string());
+    }
+    args.push(tag_name.clone());
+    if let Some(msg) = message.or(signing.tags.then_some(tag_name.as_str())) {
+        args.extend(["-m".to_string(), msg.to_string()]);
+    }
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    plan.git_with_options(&signing_options(signing, signing.tags), &args);
+    tag_name
+}
+
+/// `-c gpg.format=...` when signing with a format set in grubble's config
+fn signing_options(signing: &SigningConfig, sign: bool) -> Vec<String> {
+    match signing.format {
+        Some(format) if sign => vec!["-c".to_string(), format!("gpg.format={}", format.as_str())],
+        _ => Vec::new(),
+    }
+}
+
+fn git_config(key: &str) -> Option<String> {
+    run_git_command(&["config", "--get", key])
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+/// Fail before changing anything when signing is enabled but git has no key
+/// to sign with
+pub fn check_signing(config: &Config) -> BumperResult<()> {
+    let signing = &config.signing;
+    let signs_tags = signing.tags && config.tag;
+    if !signing.commits && !signs_tags {
+        return Ok(());
+    }
+
+    let format = match signing.format {
+        Some(format) => format,
+        None => match git_config("gpg.format").as_deref() {
+            Some("ssh") => SigningFormat::Ssh,
+            Some("x509") => SigningFormat::X509,
+            _ => SigningFormat::Openpgp,
+        },
+    };
+    let key = signing
+        .key
+        .clone()
+        .or_else(|| git_config("user.signingkey"));
+
+    if format == SigningFormat::Ssh {
+        return match key {
+            Some(_) => Ok(()),
+            None => Err(BumperError::SigningNotConfigured(
+                "SSH signing needs a key, set signing.key or git's user.signingKey".to_string(),
+            )),
+        };
     }
 
-    Ok(())
+    // Without a key git signs as the committer, which grubble sets
+    let identity = key.unwrap_or_else(|| config.git_user_email.clone());
+    let program = match format {
+        SigningFormat::X509 => {
+            git_config("gpg.x509.program").unwrap_or_else(|| "gpgsm".to_string())
+        }
+        _ => git_config("gpg.program").unwrap_or_else(|| "gpg".to_string()),
+    };
+    let found = Command::new(&program)
+        .args(["--list-secret-keys", &identity])
+        .output()
+        .map(|output| output.status.success() && !output.stdout.is_empty());
+    match found {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(BumperError::SigningNotConfigured(format!(
+            "{} has no secret key for {}, set signing.key or git's user.signingKey",
+            program, identity
+        ))),
+        Err(e) => Err(BumperError::SigningNotConfigured(format!(
+            "cannot run {}: {}",
+            program, e
+        ))),
+    }
 }
 
-pub fn set_git_config(user_name: &str, user_email: &str) -> BumperResult<()> {
+pub fn set_git_config(plan: &mut ReleasePlan, user_name: &str, user_email: &str) {
     if !user_name.is_empty() {
-        run_git_command(&["config", "user.name", user_name])?;
+        plan.git(&["config", "user.name", user_name]);
     }
     if !user_email.is_empty() {
-        run_git_command(&["config", "user.email", user_email])?;
+        plan.git(&["config", "user.email", user_email]);
     }
-    Ok(())
 }
 
-pub fn push() -> BumperResult<()> {
-    run_git_command(&["push"])?;
-    run_git_command(&["push", "--tags"])?;
-    Ok(())
+/// Push the release commit to `branch` on `remote` along with the tags this
+/// release created, and nothing else. The push is atomic: either every ref
+/// is updated or none is. New tags are never forced, so an
+/// existing release tag on the remote is left alone. Movable tags are forced
+/// only if the remote still has them where the local tag pointed before this
+/// release (`--force-with-lease`), which is read while planning.
+pub fn push(
+    plan: &mut ReleasePlan,
+    remote: &str,
+    branch: Option<&str>,
+    tags: &[String],
+    movable: &[String],
+) {
+    let mut args = vec!["push".to_string(), "--atomic".to_string()];
+    for tag in movable {
+        let previous =
+            run_git_command(&["rev-parse", "-q", "--verify", &format!("refs/tags/{}", tag)])
+                .unwrap_or_default();
+        args.push(format!("--force-with-lease=refs/tags/{}:{}", tag, previous));
+    }
+    args.push(remote.to_string());
+    args.push(match branch {
+        Some(branch) => format!("HEAD:refs/heads/{}", branch),
+        None => "HEAD".to_string(),
+    });
+    args.extend(tags.iter().map(|tag| format!("refs/tags/{}", tag)));
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    plan.git(&args);
 }
 
 /// Update major and/or minor version tags to point to the current commit.
-/// Creates lightweight tags that can be force-pushed to update on remote.
+/// Creates lightweight tags that `push` updates on the remote with a lease.
 /// This is useful for GitHub Actions and libraries that want users to reference
 /// by major version (e.g., @v4) and automatically get the latest release.
 ///
@@ -128,41 +474,341 @@ pub fn push() -> BumperResult<()> {
 /// * `tag_prefix` - Prefix for tags (typically "v")
 /// * `update_major` - Whether to create/update the major version tag (e.g., v4)
 /// * `update_minor` - Whether to create/update the minor version tag (e.g., v4.1)
+///
+/// Returns the names of the tags that will be moved.
 pub fn update_movable_tags(
+    plan: &mut ReleasePlan,
     version: &Version,
     tag_prefix: &str,
     update_major: bool,
     update_minor: bool,
-) -> BumperResult<()> {
-    if update_major {
-        let major_tag = format!("{}{}", tag_prefix, version.major);
-        // Delete local tag if exists (ignore errors if it doesn't exist)
-        let _ = run_git_command(&["tag", "-d", &major_tag]);
-        // Create new lightweight tag pointing to current commit
-        run_git_command(&["tag", &major_tag])?;
+    existing: &[Version],
+) -> Vec<String> {
+    let mut moved = Vec::new();
+    // A maintenance release on an older line leaves a tag alone when a newer
+    // release in that tag's line exists
+    let newest_in_line = |same_line: &dyn Fn(&Version) -> bool| {
+        !existing
+            .iter()
+            .any(|other| !other.is_prerelease() && same_line(other) && other > version)
+    };
+
+    if update_major && newest_in_line(&|other| other.major == version.major) {
+        moved.push(format!("{}{}", tag_prefix, version.major));
+    }
+    if update_minor
+        && newest_in_line(&|other| other.major == version.major && other.minor == version.minor)
+    {
+        moved.push(format!("{}{}.{}", tag_prefix, version.major, version.minor));
     }
 
-    if update_minor {
-        let minor_tag = format!("{}{}.{}", tag_prefix, version.major, version.minor);
-        // Delete local tag if exists (ignore errors if it doesn't exist)
-        let _ = run_git_command(&["tag", "-d", &minor_tag]);
-        // Create new lightweight tag pointing to current commit
-        run_git_command(&["tag", &minor_tag])?;
+    for tag in &moved {
+        // Lightweight tag pointing to the current commit, replacing any existing one
+        plan.git(&["tag", "-f", tag]);
     }
 
-    Ok(())
+    moved
 }
 
-/// Push commits and force-push tags to remote.
-/// Uses --force to allow major/minor version tags to be updated to point
-/// to newer commits. This is necessary for maintaining moving tags.
-///
-/// # Warning
-/// Force-pushing tags can affect users who have those tags checked out locally.
-/// Only use this when maintaining moving major/minor version tags.
-pub fn push_with_force_tags() -> BumperResult<()> {
-    run_git_command(&["push"])?;
-    // Force push tags to update major/minor tags on remote
-    run_git_command(&["push", "--tags", "--force"])?;
-    Ok(())
+/// Throwaway repositories for tests. Git commands on the thread that made
+/// one run inside it, so tests stay independent of the process's working
+/// directory and of each other.
+#[cfg(test)]
+pub mod test_repo {
+    use std::cell::RefCell;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    thread_local! {
+        static CURRENT: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+    }
+
+    pub(super) fn current() -> Option<PathBuf> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    pub struct TestRepo {
+        dir: TempDir,
+    }
+
+    impl TestRepo {
+        /// An empty repository on `main`, used by git commands on this thread
+        /// until it is dropped
+        pub fn new() -> Self {
+            Self::create(&["init", "-q", "-b", "main"])
+        }
+
+        /// A clone of `origin` made with the extra `args`, e.g. `--depth=1`,
+        /// used by git commands on this thread until it is dropped
+        pub fn clone_of(origin: &TestRepo, args: &[&str]) -> Self {
+            let url = format!("file://{}", origin.root().display());
+            let mut clone = vec!["clone", "-q"];
+            clone.extend(args);
+            clone.extend([url.as_str(), "."]);
+            Self::create(&clone)
+        }
+
+        fn create(init: &[&str]) -> Self {
+            let repo = TestRepo {
+                dir: TempDir::new().unwrap(),
+            };
+            repo.git(init);
+            repo.git(&["config", "user.name", "Test"]);
+            repo.git(&["config", "user.email", "test@example.com"]);
+            repo.git(&["config", "commit.gpgsign", "false"]);
+            repo.git(&["config", "tag.gpgsign", "false"]);
+            CURRENT.with(|current| *current.borrow_mut() = Some(repo.dir.path().to_path_buf()));
+            repo
+        }
+
+        pub fn path(&self, name: &str) -> PathBuf {
+            self.dir.path().join(name)
+        }
+
+        pub fn root(&self) -> &Path {
+            self.dir.path()
+        }
+
+        pub fn write(&self, name: &str, contents: &str) {
+            fs::write(self.path(name), contents).unwrap();
+        }
+
+        /// Write `name` and commit it with `message`
+        pub fn commit(&self, name: &str, contents: &str, message: &str) {
+            self.write(name, contents);
+            self.git(&["add", name]);
+            self.git(&["commit", "-q", "-m", message]);
+        }
+
+        /// Run git in the repository, panicking if it fails
+        pub fn git(&self, args: &[&str]) -> String {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(self.dir.path())
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "git {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+    }
+
+    impl Drop for TestRepo {
+        fn drop(&mut self) {
+            CURRENT.with(|current| *current.borrow_mut() = None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_repo::TestRepo;
+    use super::*;
+
+    #[test]
+    fn test_parse_log_keeps_bodies() {
+        let output = "abc1234\x1ffeat: add thing\n\nBREAKING CHANGE: removed old thing\n\x1e\ndef5678\x1ffix: bug\n\x1e";
+
+        let commits = parse_log(output);
+
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "abc1234");
+        assert_eq!(commits[0].subject(), "feat: add thing");
+        assert_eq!(
+            commits[0].message,
+            "feat: add thing\n\nBREAKING CHANGE: removed old thing"
+        );
+        assert_eq!(commits[0].body(), "\nBREAKING CHANGE: removed old thing");
+        assert_eq!(commits[1].subject(), "fix: bug");
+        assert_eq!(commits[1].body(), "");
+    }
+
+    #[test]
+    fn test_changed_and_tracked_files() {
+        let repo = TestRepo::new();
+        repo.commit("tracked.txt", "1", "feat: start");
+        repo.write("untracked.txt", "1");
+
+        assert!(changed_files().unwrap().is_empty());
+        assert!(is_tracked("tracked.txt"));
+        assert!(!is_tracked("untracked.txt"));
+
+        repo.write("tracked.txt", "2");
+        assert_eq!(changed_files().unwrap(), vec!["tracked.txt"]);
+        repo.git(&["add", "tracked.txt"]);
+        assert_eq!(changed_files().unwrap(), vec!["tracked.txt"]);
+    }
+
+    #[test]
+    fn test_is_ancestor() {
+        let repo = TestRepo::new();
+        repo.commit("notes.txt", "1", "feat: start");
+        let first = repo.git(&["rev-parse", "HEAD"]);
+        repo.commit("notes.txt", "2", "fix: later");
+
+        assert!(is_ancestor(&first, "HEAD"));
+        assert!(!is_ancestor("HEAD", &first));
+        assert!(!is_ancestor(&"0".repeat(40), "HEAD"));
+    }
+
+    #[test]
+    fn test_remote_refs() {
+        let origin = TestRepo::new();
+        origin.commit("notes.txt", "1", "feat: start");
+        origin.git(&["tag", "v1.0.0"]);
+        let head = origin.git(&["rev-parse", "HEAD"]);
+        let _clone = TestRepo::clone_of(&origin, &[]);
+
+        let wanted = [
+            "refs/heads/main".to_string(),
+            "refs/tags/v1.0.0".to_string(),
+            "refs/tags/v2.0.0".to_string(),
+        ];
+        let found = remote_refs("origin", &wanted).unwrap();
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found["refs/heads/main"], head);
+        assert!(found.contains_key("refs/tags/v1.0.0"));
+        assert!(remote_refs("nowhere", &wanted).is_err());
+    }
+
+    #[test]
+    fn test_branch_from_env() {
+        let github_pr = |name: &str| match name {
+            "GITHUB_HEAD_REF" => Some("feature/login".to_string()),
+            "GITHUB_REF_NAME" => Some("42/merge".to_string()),
+            _ => None,
+        };
+        let jenkins = |name: &str| (name == "GIT_BRANCH").then(|| "origin/main".to_string());
+        let github_push = |name: &str| match name {
+            "GITHUB_HEAD_REF" => Some(String::new()),
+            "GITHUB_REF_NAME" => Some("next".to_string()),
+            _ => None,
+        };
+
+        assert_eq!(branch_from_env(github_pr).as_deref(), Some("feature/login"));
+        assert_eq!(branch_from_env(jenkins).as_deref(), Some("main"));
+        assert_eq!(branch_from_env(github_push).as_deref(), Some("next"));
+        assert_eq!(branch_from_env(|_| None), None);
+    }
+
+    #[test]
+    fn test_select_last_tag_by_precedence() {
+        let tags = [
+            "v4",
+            "v4.1",
+            "deploy-prod",
+            "v4.1.0",
+            "v4.10.0",
+            "v4.9.2",
+            "v5.0.0-next.1",
+            "app-v9.0.0",
+        ];
+
+        assert_eq!(
+            select_last_tag(tags, "v", None, false).as_deref(),
+            Some("v4.10.0")
+        );
+        assert_eq!(
+            select_last_tag(tags, "v", None, true).as_deref(),
+            Some("v5.0.0-next.1")
+        );
+        let range = "4.9.x".parse().unwrap();
+        assert_eq!(
+            select_last_tag(tags, "v", Some(&range), false).as_deref(),
+            Some("v4.9.2")
+        );
+        assert_eq!(select_last_tag(["v4", "latest"], "v", None, false), None);
+    }
+
+    #[test]
+    fn test_movable_tags_stay_on_newest_release_in_line() {
+        let existing: Vec<Version> = ["2.4.0", "2.5.0", "3.0.0", "2.6.0-next.1"]
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect();
+        let version = Version::parse("2.4.1").unwrap();
+
+        let mut plan = ReleasePlan::default();
+        let moved = update_movable_tags(&mut plan, &version, "v", true, true, &existing);
+        assert_eq!(moved, vec!["v2.4"]);
+
+        let version = Version::parse("2.5.1").unwrap();
+        let moved = update_movable_tags(&mut plan, &version, "v", true, true, &existing);
+        assert_eq!(moved, vec!["v2", "v2.5"]);
+    }
+
+    #[test]
+    fn test_signed_commit_and_tag_arguments() {
+        let signing = SigningConfig {
+            commits: true,
+            tags: true,
+            key: Some("/home/ci/.ssh/release.pub".to_string()),
+            format: Some(SigningFormat::Ssh),
+        };
+        let mut plan = ReleasePlan::default();
+
+        commit_changes(
+            &mut plan,
+            "1.1.0",
+            &["a".to_string()],
+            "chore: bump",
+            &signing,
+        );
+        create_tag(&mut plan, "1.1.0", "v", None, &signing);
+
+        assert!(plan.runs_git("commit"));
+        assert!(plan.runs_git("tag"));
+        let described = plan.describe();
+        assert!(described.contains(
+            "$ git -c gpg.format=ssh commit --gpg-sign=/home/ci/.ssh/release.pub -m 'chore: bump to 1.1.0'\n"
+        ));
+        assert!(described.contains(
+            "$ git -c gpg.format=ssh tag --local-user=/home/ci/.ssh/release.pub v1.1.0 -m v1.1.0\n"
+        ));
+
+        let mut plan = ReleasePlan::default();
+        create_tag(&mut plan, "1.1.0", "v", None, &SigningConfig::default());
+        create_tag(
+            &mut plan,
+            "1.1.0",
+            "v",
+            Some("notes"),
+            &SigningConfig::default(),
+        );
+        assert_eq!(
+            plan.describe(),
+            "$ git tag v1.1.0\n$ git tag -a v1.1.0 -m notes\n"
+        );
+    }
+
+    #[test]
+    fn test_push_names_only_release_refs() {
+        let mut plan = ReleasePlan::default();
+        let tags = ["x9.0.0".to_string(), "x9".to_string()];
+
+        push(
+            &mut plan,
+            "upstream",
+            Some("release/9.x"),
+            &tags,
+            &tags[1..],
+        );
+
+        assert_eq!(
+            plan.describe(),
+            "$ git push --atomic --force-with-lease=refs/tags/x9: upstream HEAD:refs/heads/release/9.x refs/tags/x9.0.0 refs/tags/x9\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_log_empty() {
+        assert!(parse_log("").is_empty());
+    }
 }
@@ -0,0 +1,186 @@
+use crate::config::{Config, ShallowClone};
+use crate::error::{BumperError, BumperResult};
+use crate::git;
+
+/// Commits fetched at most by doubling `--deepen` before fetching the whole
+/// history instead
+const MAX_DEEPEN: u32 = 1600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fetch {
+    Deepen(u32),
+    Unshallow,
+}
+
+/// Whether this is a shallow clone whose history stops before the last
+/// release tag, so every commit in it would count towards the next release.
+/// Without release tags on the remote this is the first release and the
+/// history holds everything that counts. A remote that can't be asked is
+/// assumed to have them.
+pub fn is_truncated(config: &Config) -> BumperResult<bool> {
+    if !git::is_shallow() || git::get_last_tag(config)?.is_some() {
+        return Ok(false);
+    }
+    Ok(git::get_remote_last_tag(config).map_or(true, |tag| tag.is_some()))
+}
+
+/// Make sure commits since the last release can be analysed: fail in a
+/// truncated shallow clone, or fetch the missing history with
+/// `shallowClone: deepen`
+pub fn ensure(config: &Config) -> BumperResult<()> {
+    match config.shallow_clone {
+        ShallowClone::Deepen => deepen(config),
+        ShallowClone::Fail if is_truncated(config)? => Err(BumperError::ShallowClone),
+        ShallowClone::Fail => Ok(()),
+    }
+}
+
+/// With `shallowClone: deepen`, fetch tags and then history in growing steps
+/// until the last release tag is reachable, and the full history as a last
+/// resort
+pub fn deepen(config: &Config) -> BumperResult<()> {
+    if config.shallow_clone != ShallowClone::Deepen || !is_truncated(config)? {
+        return Ok(());
+    }
+
+    eprintln!("Shallow clone without a release tag in reach, fetching tags and history...");
+    git::fetch_tags(&config.remote)?;
+    fetch_until(
+        || Ok(!git::is_shallow() || git::get_last_tag(config)?.is_some()),
+        |fetch| match fetch {
+            Fetch::Deepen(depth) => git::deepen(&config.remote, depth),
+            Fetch::Unshallow => git::unshallow(&config.remote),
+        },
+    )
+}
+
+/// Fetch more history, doubling the step each time, until `reached`
+fn fetch_until(
+    mut reached: impl FnMut() -> BumperResult<bool>,
+    mut fetch: impl FnMut(Fetch) -> BumperResult<()>,
+) -> BumperResult<()> {
+    let mut depth = 50;
+    while !reached()? {
+        if depth > MAX_DEEPEN {
+            return fetch(Fetch::Unshallow);
+        }
+        fetch(Fetch::Deepen(depth))?;
+        depth *= 2;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_repo::TestRepo;
+    use std::cell::RefCell;
+
+    /// A remote with `count` commits, tagged `v1.0.0` at the second
+    fn origin(count: usize) -> TestRepo {
+        let origin = TestRepo::new();
+        for n in 0..count {
+            origin.commit("notes.txt", &n.to_string(), &format!("fix: change {}", n));
+            if n == 1 {
+                origin.git(&["tag", "v1.0.0"]);
+            }
+        }
+        origin
+    }
+
+    fn config(shallow_clone: ShallowClone) -> Config {
+        Config {
+            shallow_clone,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_fetch_until_doubles_then_unshallows() {
+        let mut fetched = Vec::new();
+        fetch_until(
+            || Ok(false),
+            |fetch| {
+                fetched.push(fetch);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            fetched,
+            vec![
+                Fetch::Deepen(50),
+                Fetch::Deepen(100),
+                Fetch::Deepen(200),
+                Fetch::Deepen(400),
+                Fetch::Deepen(800),
+                Fetch::Deepen(MAX_DEEPEN),
+                Fetch::Unshallow,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fetch_until_stops_once_reached() {
+        // Reachable after the second fetch
+        let fetched = RefCell::new(Vec::new());
+        fetch_until(
+            || Ok(fetched.borrow().len() == 2),
+            |fetch| {
+                fetched.borrow_mut().push(fetch);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            fetched.into_inner(),
+            vec![Fetch::Deepen(50), Fetch::Deepen(100)]
+        );
+
+        let mut fetches = 0;
+        fetch_until(
+            || Ok(true),
+            |_| {
+                fetches += 1;
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(fetches, 0);
+    }
+
+    #[test]
+    fn test_truncated_clone_fails_or_deepens() {
+        let origin = origin(5);
+        let clone = TestRepo::clone_of(&origin, &["--depth=1"]);
+
+        assert!(is_truncated(&config(ShallowClone::Fail)).unwrap());
+        assert!(matches!(
+            ensure(&config(ShallowClone::Fail)),
+            Err(BumperError::ShallowClone)
+        ));
+
+        ensure(&config(ShallowClone::Deepen)).unwrap();
+
+        assert_eq!(
+            git::get_last_tag(&Config::default()).unwrap().as_deref(),
+            Some("v1.0.0")
+        );
+        assert_eq!(
+            clone.git(&["rev-parse", "--is-shallow-repository"]),
+            "false"
+        );
+    }
+
+    #[test]
+    fn test_first_release_in_shallow_clone() {
+        let origin = TestRepo::new();
+        origin.commit("notes.txt", "0", "feat: start");
+        let _clone = TestRepo::clone_of(&origin, &["--depth=1"]);
+
+        assert!(git::is_shallow());
+        assert!(!is_truncated(&config(ShallowClone::Fail)).unwrap());
+        ensure(&config(ShallowClone::Fail)).unwrap();
+    }
+}
@@ -0,0 +1,329 @@
+use crate::config::CONFIG_FILE;
+use crate::error::{BumperError, BumperResult};
+use crate::git;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+/// What `grubble init` found in the repository
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Detected {
+    cargo_toml: bool,
+    package_json: bool,
+    pyproject_toml: bool,
+    changelog: bool,
+    /// Most common prefix of existing version tags, and how many use it
+    tag_prefix: Option<(String, usize)>,
+}
+
+impl Detected {
+    fn inspect(dir: &Path, tags: &[String]) -> Self {
+        Detected {
+            cargo_toml: dir.join("Cargo.toml").exists(),
+            package_json: dir.join("package.json").exists(),
+            pyproject_toml: dir.join("pyproject.toml").exists(),
+            changelog: dir.join("CHANGELOG.md").exists(),
+            tag_prefix: detect_tag_prefix(tags),
+        }
+    }
+
+    fn preset(&self) -> &'static str {
+        if self.cargo_toml {
+            "rust"
+        } else if self.package_json {
+            "node"
+        } else {
+            "git"
+        }
+    }
+}
+
+/// The choices written to the starter file
+#[derive(Debug, PartialEq, Eq)]
+struct Answers {
+    preset: String,
+    tag_prefix: String,
+    tag: bool,
+    push: bool,
+    changelog: bool,
+}
+
+impl Answers {
+    fn proposed(detected: &Detected) -> Self {
+        Answers {
+            preset: detected.preset().to_string(),
+            tag_prefix: detected
+                .tag_prefix
+                .as_ref()
+                .map_or("v".to_string(), |(prefix, _)| prefix.clone()),
+            tag: true,
+            push: false,
+            changelog: detected.changelog,
+        }
+    }
+}
+
+/// Find the prefix most existing `<prefix>X.Y.Z` tags share
+fn detect_tag_prefix(tags: &[String]) -> Option<(String, usize)> {
+    let version_tag = Regex::new(r"^(.*?)\d+\.\d+\.\d+$").unwrap();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for tag in tags {
+        if let Some(captures) = version_tag.captures(tag) {
+            *counts.entry(captures.get(1).unwrap().as_str()).or_default() += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(prefix, count)| (prefix.to_string(), count))
+}
+
+fn package_files(preset: &str) -> Vec<&'static str> {
+    match preset {
+        "rust" => vec!["Cargo.toml"],
+        "node" => vec!["package.json"],
+        _ => vec![],
+    }
+}
+
+/// The starter file's contents, in the order they are written
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Starter<'a> {
+    preset: &'a str,
+    package_files: Vec<&'static str>,
+    tag_prefix: &'a str,
+    commit_prefix: &'static str,
+    tag: bool,
+    push: bool,
+    changelog: bool,
+}
+
+/// Render the starter config as plain JSON, so other tools reading
+/// `.versionrc.json` can parse it too
+fn render(answers: &Answers) -> BumperResult<String> {
+    let starter = Starter {
+        preset: &answers.preset,
+        package_files: package_files(&answers.preset),
+        tag_prefix: &answers.tag_prefix,
+        commit_prefix: "chore: bump version",
+        tag: answers.tag,
+        push: answers.push,
+        changelog: answers.changelog,
+    };
+    Ok(serde_json::to_string_pretty(&starter)? + "\n")
+}
+
+/// What the written settings do, printed once the file is created
+fn notes(answers: &Answers, detected: &Detected) -> Vec<String> {
+    let mut notes = vec![match answers.preset.as_str() {
+        "rust" => "preset: versions come from Cargo.toml".to_string(),
+        "node" => "preset: versions come from package.json".to_string(),
+        _ => "preset: versions come from tags only".to_string(),
+    }];
+    if detected.pyproject_toml {
+        notes.push("pyproject.toml has no preset yet, so its version is not updated".to_string());
+    }
+    notes.push(match &detected.tag_prefix {
+        Some((prefix, count)) if *prefix == answers.tag_prefix => format!(
+            "tagPrefix: matches {} existing tag(s) such as {}1.0.0",
+            count, prefix
+        ),
+        _ => format!("tagPrefix: tags look like {}1.0.0", answers.tag_prefix),
+    });
+    notes.push(if answers.tag {
+        "tag: each release gets a git tag".to_string()
+    } else {
+        "tag: releases are not tagged".to_string()
+    });
+    notes.push(if answers.push {
+        "push: commits and tags are pushed".to_string()
+    } else {
+        "push: nothing is pushed, run git push after a release".to_string()
+    });
+    notes.push(if answers.changelog {
+        "changelog: CHANGELOG.md is updated from conventional commits".to_string()
+    } else {
+        "changelog: no CHANGELOG.md is maintained".to_string()
+    });
+    notes
+}
+
+/// Read one trimmed line from stdin, empty at end of input
+fn read_answer(prompt: &str) -> BumperResult<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+fn ask(question: &str, default: &str) -> BumperResult<String> {
+    let answer = read_answer(&format!("{} [{}]: ", question, default))?;
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer
+    })
+}
+
+fn confirm(question: &str, default: bool) -> BumperResult<bool> {
+    let hint = if default { "Y/n" } else { "y/N" };
+    loop {
+        match read_answer(&format!("{} [{}]: ", question, hint))?
+            .to_lowercase()
+            .as_str()
+        {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("Please answer y or n."),
+        }
+    }
+}
+
+/// Inspect the repository, ask about the proposed settings (unless `yes`)
+/// and write a starter `.versionrc.json`
+pub fn run(force: bool, yes: bool) -> BumperResult<()> {
+    let path = Path::new(CONFIG_FILE);
+    if path.exists() && !force {
+        return Err(BumperError::ConfigError(format!(
+            "{} already exists (use --force to overwrite)",
+            CONFIG_FILE
+        )));
+    }
+
+    let tags = if git::is_repository() {
+        git::list_tags()?
+    } else {
+        Vec::new()
+    };
+    let detected = Detected::inspect(Path::new("."), &tags);
+    let mut answers = Answers::proposed(&detected);
+
+    let interactive = !yes && io::stdin().is_terminal();
+    if interactive {
+        if detected.cargo_toml {
+            println!("Found Cargo.toml");
+        }
+        if detected.package_json {
+            println!("Found package.json");
+        }
+        if detected.pyproject_toml {
+            println!("Found pyproject.toml (no preset yet, versions will come from tags)");
+        }
+        if let Some((prefix, count)) = &detected.tag_prefix {
+            println!("Found {} tag(s) with prefix '{}'", count, prefix);
+        }
+        if detected.changelog {
+            println!("Found CHANGELOG.md");
+        }
+
+        answers.preset = loop {
+            let preset = ask("Preset (rust, node, git)", &answers.preset)?;
+            if matches!(preset.as_str(), "rust" | "node" | "git") {
+                break preset;
+            }
+            println!("Please choose rust, node or git.");
+        };
+        answers.tag_prefix = ask("Tag prefix", &answers.tag_prefix)?;
+        answers.tag = confirm("Create a tag for each release?", answers.tag)?;
+        answers.push = confirm("Push commits and tags?", answers.push)?;
+        answers.changelog = confirm("Maintain CHANGELOG.md?", answers.changelog)?;
+    }
+
+    fs::write(path, render(&answers)?)?;
+
+    println!(
+        "Created {} (preset {}, tag prefix '{}')",
+        CONFIG_FILE, answers.preset, answers.tag_prefix
+    );
+    for note in notes(&answers, &detected) {
+        println!("  {}", note);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigLayers, ConfigSource, Origin};
+    use tempfile::TempDir;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect_tag_prefix_picks_most_common() {
+        let found = detect_tag_prefix(&tags(&[
+            "release-1.0.0",
+            "release-1.1.0",
+            "v0.9.0",
+            "v1",
+            "nightly",
+        ]));
+
+        assert_eq!(found, Some(("release-".to_string(), 2)));
+        assert_eq!(
+            detect_tag_prefix(&tags(&["1.0.0"])),
+            Some((String::new(), 1))
+        );
+        assert_eq!(detect_tag_prefix(&[]), None);
+    }
+
+    #[test]
+    fn test_detect_proposes_preset_from_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("package.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join("CHANGELOG.md"), "# Changelog\n").unwrap();
+
+        let detected = Detected::inspect(temp_dir.path(), &tags(&["v1.2.3"]));
+        let answers = Answers::proposed(&detected);
+
+        assert_eq!(answers.preset, "node");
+        assert_eq!(answers.tag_prefix, "v");
+        assert!(answers.changelog);
+    }
+
+    #[test]
+    fn test_rendered_starter_loads_as_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let detected = Detected {
+            cargo_toml: true,
+            pyproject_toml: true,
+            tag_prefix: Some(("release-".to_string(), 3)),
+            ..Detected::default()
+        };
+        let answers = Answers::proposed(&detected);
+        let path = temp_dir.path().join(CONFIG_FILE);
+        let rendered = render(&answers).unwrap();
+        serde_json::from_str::<serde_json::Value>(&rendered).expect("starter is plain JSON");
+        fs::write(&path, rendered).unwrap();
+
+        let mut layers = ConfigLayers::new();
+        layers
+            .add_file(&ConfigSource::for_path(&path), Origin::Repo(path.clone()))
+            .unwrap();
+        let config = layers.config().unwrap();
+
+        assert_eq!(config.preset, "rust");
+        assert_eq!(config.package_files, vec!["Cargo.toml"]);
+        assert_eq!(config.tag_prefix, "release-");
+        assert!(config.tag);
+        assert!(!config.push);
+
+        let notes = notes(&answers, &detected);
+        assert!(notes
+            .iter()
+            .any(|n| n.contains("pyproject.toml has no preset")));
+        assert!(notes
+            .iter()
+            .any(|n| n.contains("matches 3 existing tag(s)")));
+    }
+}
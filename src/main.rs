@@ -1,145 +1,360 @@
-use clap::Parser;
 use std::process;
 
 mod analyser;
+mod branch;
 mod changelog;
+mod cli;
 mod config;
 mod error;
 mod git;
+mod history;
+mod init;
+mod plan;
+mod preflight;
+mod report;
 mod strategy;
 mod versioner;
 
-use analyser::{analyse_commits, BumpType};
-use config::Config;
-use error::BumperResult;
-use strategy::load_strategy;
-
-#[derive(Parser, Debug)]
-#[command(name = "bump")]
-#[command(about = "Automatic semantic versioning based on conventional commits", long_about = None)]
-struct Args {
-    /// Push changes to remote
-    #[arg(short, long)]
-    push: bool,
-
-    /// Suppress commit list output
-    #[arg(short, long)]
-    quiet: bool,
-
-    /// Create git tag for the version
-    #[arg(short, long)]
-    tag: bool,
-
-    /// Include release notes in the git tag annotation
-    #[arg(short = 'r', long)]
-    release_notes: bool,
-
-    /// Output only the new version string (dry run, no changes)
-    #[arg(long)]
-    raw: bool,
-
-    /// Versioning strategy (node, rust, git)
-    #[arg(long)]
-    preset: Option<String>,
-
-    /// Prefix for git tags (default: v)
-    #[arg(long)]
-    tag_prefix: Option<String>,
-
-    /// Prefix for commit messages
-    #[arg(long)]
-    commit_prefix: Option<String>,
-
-    /// Comma-separated list of files to update (for node/rust preset)
-    #[arg(long)]
-    package_files: Option<String>,
-
-    /// Git user name for commits
-    #[arg(long)]
-    git_user_name: Option<String>,
-
-    /// Git user email for commits
-    #[arg(long)]
-    git_user_email: Option<String>,
-
-    /// Update major version tag (e.g., v4 -> v4.x.x)
-    #[arg(long)]
-    update_major_tag: bool,
-
-    /// Update minor version tag (e.g., v4.1 -> v4.1.x)
-    #[arg(long)]
-    update_minor_tag: bool,
-
-    /// Generate and maintain a CHANGELOG.md file
-    #[arg(long)]
-    changelog: bool,
+use analyser::{analyse_commits, AnalysisResult, BumpType};
+use branch::BranchPolicy;
+use cli::{Cli, Command, ConfigCommand, OutputFormat, PreviewFormat};
+use config::{Config, ConfigLayers, OtherBranches, ShallowClone, TagStyle};
+use error::{BumperError, BumperResult};
+use plan::{FileUpdate, ReleasePlan};
+use report::{CheckStatus, Report, VerifyReport};
+use strategy::{load_strategy, Strategy};
+use versioner::{ReleaseAs, Version};
+
+/// Where human-readable progress messages go. With `--output json` they move
+/// to stderr so stdout only carries the document.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LogTarget {
+    Stdout,
+    Stderr,
+    Silent,
 }
 
-fn log(msg: &str, is_raw: bool) {
-    if !is_raw {
-        println!("{}", msg);
+fn log(msg: &str, target: LogTarget) {
+    match target {
+        LogTarget::Stdout => println!("{}", msg),
+        LogTarget::Stderr => eprintln!("{}", msg),
+        LogTarget::Silent => {}
     }
 }
 
-fn run() -> BumperResult<()> {
-    let args = Args::parse();
+/// Repository state relevant to the next release, read without modifying anything
+struct Pending {
+    current_version: Version,
+    last_tag: Option<String>,
+    commits: Vec<git::Commit>,
+    analysis: AnalysisResult,
+    /// The bump to apply, after the `initialDevelopment` rules
+    bump: BumpType,
+    next_version: Version,
+    branch: Option<String>,
+}
 
-    // Load config from file
-    let mut config = Config::load();
+impl Pending {
+    fn into_report(self) -> Report {
+        Report {
+            previous_version: Some(self.current_version),
+            new_version: Some(self.next_version),
+            bump_type: self.bump,
+            last_tag: self.last_tag,
+            commits: self.analysis.commits,
+            triggering_commits: self.analysis.triggering_commits,
+            unknown_commits: self.analysis.unknown_commits,
+            branch: self.branch,
+            ..Report::default()
+        }
+    }
+}
 
-    // Override with CLI arguments
-    if let Some(preset) = args.preset {
-        config.preset = preset;
+fn run() -> BumperResult<()> {
+    let cli = Cli::try_parse_checked(std::env::args_os()).unwrap_or_else(|e| e.exit());
+
+    let output = cli.output;
+    match &cli.command {
+        // Init writes the config file, so it must not require a valid one
+        Some(Command::Init { force, yes }) => init::run(*force, *yes),
+        Some(Command::Config(ConfigCommand::Show)) => show_config(&load_layers(&cli)?, output),
+        Some(Command::Current) => print_current(&load_config(&cli)?, output),
+        Some(Command::Next(_)) => {
+            let config = load_config(&cli)?;
+            history::ensure(&config)?;
+            print_next(&config, output)
+        }
+        Some(Command::Explain(_)) => {
+            let config = load_config(&cli)?;
+            history::ensure(&config)?;
+            explain(&config, output)
+        }
+        Some(Command::Bump(args)) => {
+            let config = load_config(&cli)?;
+            // A truncated history is reported with the other pre-flight
+            // problems, so only deepen it here
+            history::deepen(&config)?;
+            bump(config, false, args.dry_run, output)
+        }
+        Some(Command::Changelog { format, .. }) => {
+            let config = load_config(&cli)?;
+            history::ensure(&config)?;
+            preview_changelog(&config, *format, output)
+        }
+        Some(Command::Verify) => verify(&load_config(&cli)?, output),
+        None => {
+            eprintln!(
+                "Warning: running grubble without a subcommand is deprecated and will be removed in the next major version. Use `grubble bump` (or `grubble next` instead of --raw)."
+            );
+            let config = load_config(&cli)?;
+            if cli.legacy.raw {
+                history::ensure(&config)?;
+            } else {
+                history::deepen(&config)?;
+            }
+            bump(config, cli.legacy.raw, cli.legacy.bump.dry_run, output)
+        }
     }
-    if args.package_files.is_none() {
-        config.package_files = match config.preset.as_str() {
-            "rust" => vec!["Cargo.toml".to_string()],
-            "node" => vec!["package.json".to_string()],
-            "git" => vec![],
-            _ => vec!["package.json".to_string()],
-        };
+}
+
+/// Defaults, user and repo config files, environment, then flags
+fn load_layers(cli: &Cli) -> BumperResult<ConfigLayers> {
+    let mut layers = ConfigLayers::load(cli.config_file.as_deref())?;
+    layers.add_overrides(cli.config.overrides())?;
+    if let Some(args) = cli.bump_args() {
+        layers.add_overrides(args.overrides())?;
     }
-    if let Some(tag_prefix) = args.tag_prefix {
-        config.tag_prefix = tag_prefix;
+    Ok(layers)
+}
+
+/// The validated config, with the branch policy and `--release-as` applied
+fn load_config(cli: &Cli) -> BumperResult<Config> {
+    let mut config = load_layers(cli)?.config()?;
+    config.branch = BranchPolicy::resolve(&config.branches, git::current_branch()?.as_deref());
+    if let Some(args) = cli.release_args() {
+        config.release_as = args.release_as.clone();
     }
-    if let Some(commit_prefix) = args.commit_prefix {
-        config.commit_prefix = commit_prefix;
+    Ok(config)
+}
+
+/// Print the effective config and the layer each value came from. The
+/// config is validated too, so this doubles as a check of overrides.
+fn show_config(layers: &ConfigLayers, output: OutputFormat) -> BumperResult<()> {
+    layers.config()?;
+    match output {
+        OutputFormat::Text => print!("{}", report::config_table(&layers.entries())),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&layers.to_json())?),
     }
-    if let Some(package_files) = args.package_files {
-        config.package_files = package_files.split(',').map(|s| s.to_string()).collect();
+    Ok(())
+}
+
+/// Work out the current and next version without touching files, git config
+/// or history. A package version behind the latest tag is treated as the tag
+/// version, as a release would sync it.
+fn inspect(config: &Config) -> BumperResult<Pending> {
+    let strategy = load_strategy(config);
+
+    let mut current_version = strategy.get_current_version()?;
+    if let Some(tag_ver) = git::get_last_tag_version(config)? {
+        if current_version < tag_ver {
+            current_version = tag_ver;
+        }
     }
-    if args.push {
-        config.push = true;
+
+    let last_tag = git::get_last_tag(config)?;
+    let commits = git::get_commits_since_tag(last_tag.as_deref(), None)?;
+    let analysis = analyse_commits(&commits, config);
+    let computed =
+        current_version.initial_development_bump(analysis.bump, &config.initial_development);
+    let (next_version, bump) = match &config.release_as {
+        Some(release_as) => release_as.resolve(&current_version, computed)?,
+        None => (current_version.bump(computed), computed),
+    };
+    let next_version = if bump == BumpType::None {
+        current_version.clone()
+    } else {
+        config
+            .branch
+            .version(next_version, &git::tag_versions(&config.tag_prefix)?)?
+    };
+
+    Ok(Pending {
+        current_version,
+        last_tag,
+        commits,
+        analysis,
+        bump,
+        next_version,
+        branch: config.branch.branch.clone(),
+    })
+}
+
+fn print_current(config: &Config, output: OutputFormat) -> BumperResult<()> {
+    let pending = inspect(config)?;
+    if output == OutputFormat::Json {
+        return pending.into_report().print();
     }
-    if args.tag {
-        config.tag = true;
+    println!("{}", pending.current_version);
+    Ok(())
+}
+
+/// Print `<next version> <bump type>`, e.g. `1.3.0 minor`
+fn print_next(config: &Config, output: OutputFormat) -> BumperResult<()> {
+    let pending = inspect(config)?;
+    if output == OutputFormat::Json {
+        return pending.into_report().print();
     }
-    if args.release_notes {
-        config.release_notes = true;
+    println!("{} {}", pending.next_version, pending.bump.as_str());
+    Ok(())
+}
+
+/// Print a table of every commit in the release range with its
+/// classification, followed by the commits that decided the bump
+fn explain(config: &Config, output: OutputFormat) -> BumperResult<()> {
+    let pending = inspect(config)?;
+    if output == OutputFormat::Json {
+        return pending.into_report().print();
     }
-    if let Some(git_user_name) = args.git_user_name {
-        config.git_user_name = git_user_name;
+
+    if pending.commits.is_empty() {
+        println!("No commits since last tag.");
+        return Ok(());
     }
-    if let Some(git_user_email) = args.git_user_email {
-        config.git_user_email = git_user_email;
+
+    print!("{}", report::explain_table(&pending.analysis.commits));
+    println!();
+    println!(
+        "Bump: {} ({} -> {})",
+        pending.bump.as_str(),
+        pending.current_version,
+        pending.next_version
+    );
+    let computed = pending
+        .current_version
+        .initial_development_bump(pending.analysis.bump, &config.initial_development);
+    if computed != pending.analysis.bump {
+        println!(
+            "Commits call for a {} bump, lowered by the initialDevelopment rules for 0.x versions",
+            pending.analysis.bump.as_str()
+        );
     }
-    if args.update_major_tag {
-        config.update_major_tag = true;
+    match &config.release_as {
+        Some(ReleaseAs::Version(version)) => println!("Released as {} by --release-as", version),
+        Some(ReleaseAs::Bump(_)) if pending.bump != computed => println!(
+            "Commits call for a {} bump, raised by --release-as",
+            computed.as_str()
+        ),
+        _ => {}
     }
-    if args.update_minor_tag {
-        config.update_minor_tag = true;
+    if pending.analysis.bump != BumpType::None {
+        println!("Decided by:");
+        for commit in &pending.analysis.commits {
+            if commit.bump == pending.analysis.bump {
+                println!("  {} {}", commit.hash, commit.message);
+            }
+        }
     }
-    if args.changelog {
-        config.changelog = true;
+    Ok(())
+}
+
+fn bump(config: Config, is_raw: bool, dry_run: bool, output: OutputFormat) -> BumperResult<()> {
+    let target = if is_raw {
+        LogTarget::Silent
+    } else if output == OutputFormat::Json {
+        LogTarget::Stderr
+    } else {
+        LogTarget::Stdout
+    };
+
+    let policy = &config.branch;
+    let mut dry_run = dry_run;
+    if !dry_run && !is_raw {
+        if policy.unmatched == Some(OtherBranches::DryRun) {
+            log(
+                &format!(
+                    "Branch '{}' matches no release rule, so this is a dry run.",
+                    policy.branch_name()
+                ),
+                target,
+            );
+            dry_run = true;
+        }
+        policy.check_release()?;
+        git::check_signing(&config)?;
+    }
+
+    let mut report = Report {
+        dry_run,
+        branch: policy.branch.clone(),
+        ..Report::default()
+    };
+    let mut plan = ReleasePlan::default();
+    release(config.clone(), is_raw, target, &mut report, &mut plan)?;
+
+    let problems = if is_raw || report.bump_type == BumpType::None {
+        Vec::new()
+    } else {
+        preflight::check(&config, &report, &plan)?
+    };
+
+    if dry_run {
+        if !problems.is_empty() {
+            log("Pre-flight checks would stop this release:", target);
+            for problem in &problems {
+                log(&format!("  - {}", problem), target);
+            }
+        }
+        log("Dry run, nothing was changed. A release would run:", target);
+        log(plan.describe().trim_end(), target);
+    } else {
+        if !problems.is_empty() {
+            return Err(BumperError::PreflightFailed(problems));
+        }
+        plan.execute()?;
+        report.committed = plan.runs_git("commit");
+        report.pushed = plan.runs_git("push");
+        log_outcome(&report, target);
     }
 
-    let quiet = args.quiet;
+    if output == OutputFormat::Json {
+        report.print()?;
+    } else if is_raw {
+        if let Some(version) = &report.new_version {
+            println!("{}", version);
+        }
+    }
+    Ok(())
+}
 
-    let is_raw = args.raw;
+/// Summarise what an executed release did
+fn log_outcome(report: &Report, target: LogTarget) {
+    let tagged = !report.tags_created.is_empty();
+    if report.pushed {
+        let mut actions = vec!["Pushed changes"];
+        if tagged {
+            actions.push("and tags");
+        }
+        log(&format!("{}.", actions.join(" ")), target);
+    } else if report.committed || tagged {
+        let mut actions = vec!["Committed"];
+        if tagged {
+            actions.push("and tagged");
+        }
+        log(&format!("{} locally.", actions.join(" ")), target);
+    }
+}
 
-    // Force settings for raw mode
+/// Work out the release, adding every file change and git command to `plan`
+/// and recording the outcome in `report`. Nothing is written here.
+fn release(
+    mut config: Config,
+    is_raw: bool,
+    target: LogTarget,
+    report: &mut Report,
+    plan: &mut ReleasePlan,
+) -> BumperResult<()> {
+    // Raw mode only works out the version, so it changes nothing
     if is_raw {
-        config.raw = true;
         config.push = false;
         config.tag = false;
     }
@@ -147,22 +362,30 @@ fn run() -> BumperResult<()> {
     if config.release_notes && !config.tag {
         log(
             "Warning: --release-notes requires --tag to be effective.",
-            is_raw,
+            target,
+        );
+    } else if config.release_notes && config.tag_style == TagStyle::Lightweight {
+        log(
+            "Warning: --release-notes has no effect with lightweight tags.",
+            target,
         );
     }
 
     // Set git config for commits
-    git::set_git_config(&config.git_user_name, &config.git_user_email)?;
+    if !is_raw {
+        git::set_git_config(plan, &config.git_user_name, &config.git_user_email);
+    }
 
     let strategy = load_strategy(&config);
 
     let mut current_version = strategy.get_current_version()?;
-    log(&format!("Current version: {}", current_version), is_raw);
+    log(&format!("Current version: {}", current_version), target);
 
-    let last_tag = git::get_last_tag()?;
+    let last_tag = git::get_last_tag(&config)?;
+    report.last_tag = last_tag.clone();
     log(
         &format!("Last tag: {}", last_tag.as_deref().unwrap_or("none")),
-        is_raw,
+        target,
     );
 
     let last_tag_version = git::get_last_tag_version(&config)?;
@@ -175,149 +398,409 @@ fn run() -> BumperResult<()> {
                     "Package version {} is behind latest tag version {}, syncing...",
                     current_version, tag_ver
                 ),
-                is_raw,
+                target,
             );
-            let updated_files = strategy.update_files(&tag_ver)?;
+            let updated_files = if is_raw {
+                Vec::new()
+            } else {
+                stage_package(plan, strategy.as_ref(), &tag_ver)?
+            };
             if !updated_files.is_empty() {
                 git::commit_changes(
+                    plan,
                     &format!("v{}", tag_ver),
                     &updated_files,
                     "chore: sync package version",
-                )?;
-                log(&format!("Synced package to version {}", tag_ver), is_raw);
+                    &config.signing,
+                );
+                report.files_changed.extend(updated_files);
+                log(&format!("Synced package to version {}", tag_ver), target);
             }
             current_version = tag_ver;
         }
     }
 
-    let commits = git::get_commits_since_tag(last_tag.as_deref())?;
+    report.previous_version = Some(current_version.clone());
+    report.new_version = Some(current_version.clone());
+
+    let commits = git::get_commits_since_tag(last_tag.as_deref(), None)?;
 
-    if !quiet {
-        log("Commits to analyse:", is_raw);
+    if !config.quiet {
+        log("Commits to analyse:", target);
         for commit in &commits {
-            log(&format!("  - {}", commit), is_raw);
+            log(&format!("  - {}", commit.subject()), target);
         }
     }
 
-    let release_notes_message = if config.release_notes && !commits.is_empty() {
-        Some(
-            commits
-                .iter()
-                .map(|c| format!("- {}", c))
-                .collect::<Vec<_>>()
-                .join("\n"),
-        )
-    } else {
-        None
-    };
-
-    if commits.is_empty() {
-        log("No commits since last tag.", is_raw);
-        if is_raw {
-            println!("{}", current_version);
-        }
+    if commits.is_empty() && config.release_as.is_none() {
+        log("No commits since last tag.", target);
         return Ok(());
     }
 
     let analysis = analyse_commits(&commits, &config);
+    report.commits = analysis.commits.clone();
+    report.triggering_commits = analysis.triggering_commits.clone();
+    report.unknown_commits = analysis.unknown_commits.clone();
     log(
         &format!("Version bump: {}", analysis.bump.as_str().to_uppercase()),
-        is_raw,
+        target,
     );
 
-    if analysis.bump == BumpType::None {
-        log("No version bump required.", is_raw);
-        if is_raw {
-            println!("{}", current_version);
+    let computed =
+        current_version.initial_development_bump(analysis.bump, &config.initial_development);
+    if computed != analysis.bump {
+        log(
+            &format!(
+                "Version {} is in initial development, applying a {} bump instead",
+                current_version,
+                computed.as_str()
+            ),
+            target,
+        );
+    }
+    let (new_version, bump) = match &config.release_as {
+        Some(release_as) => {
+            let (version, bump) = release_as.resolve(&current_version, computed)?;
+            match release_as {
+                ReleaseAs::Version(_) => log(
+                    &format!("Releasing as {} (--release-as sets the version)", version),
+                    target,
+                ),
+                ReleaseAs::Bump(_) if bump != computed => log(
+                    &format!("Raising the bump to {} (--release-as)", bump.as_str()),
+                    target,
+                ),
+                ReleaseAs::Bump(_) => {}
+            }
+            (version, bump)
         }
+        None => (current_version.bump(computed), computed),
+    };
+    report.bump_type = bump;
+
+    if bump == BumpType::None {
+        log("No version bump required.", target);
         return Ok(());
     }
 
-    log("Triggering commits:", is_raw);
+    let policy = &config.branch;
+    let existing_versions = git::tag_versions(&config.tag_prefix)?;
+    let new_version = policy.version(new_version, &existing_versions)?;
+    if new_version.is_prerelease() {
+        log(
+            &format!(
+                "Branch '{}' releases prereleases: {}",
+                policy.branch_name(),
+                new_version
+            ),
+            target,
+        );
+    }
+
+    log("Triggering commits:", target);
     if !is_raw {
         for commit in &analysis.triggering_commits {
-            log(&format!("  - {}", commit), is_raw);
+            log(&format!("  - {}", commit), target);
         }
     }
 
     // Warn about unknown commit types
     if !analysis.unknown_commits.is_empty() && !is_raw {
-        log("Warning: The following commits have unknown or unconfigured types and did not trigger a version bump:", is_raw);
+        log("Warning: The following commits have unknown or unconfigured types and did not trigger a version bump:", target);
         for commit in &analysis.unknown_commits {
-            log(&format!("  - {}", commit), is_raw);
+            log(&format!("  - {}", commit), target);
         }
-        log("Consider configuring these types in .versionrc.json or using standard Conventional Commits types.", is_raw);
+        log("Consider configuring these types in .versionrc.json or using standard Conventional Commits types.", target);
     }
 
-    let new_version = current_version.bump(analysis.bump);
+    report.new_version = Some(new_version.clone());
 
     if is_raw {
-        println!("{}", new_version);
         return Ok(());
     }
 
-    let updated_files = strategy.update_files(&new_version)?;
-    log(&format!("Updated to {}", new_version), is_raw);
+    let released = changelog::release_timestamp(&config, source_date_epoch().as_deref())?;
+
+    let mut all_updated_files = stage_package(plan, strategy.as_ref(), &new_version)?;
+    log(&format!("Updated to {}", new_version), target);
 
     // Generate changelog if enabled
     if config.changelog {
-        changelog::generate_changelog_entry(&new_version, &commits, analysis.bump)?;
-        log("Updated CHANGELOG.md", is_raw);
-    }
+        for file in changelog::changelog_targets(&config) {
+            let path = file.path.to_string_lossy().to_string();
+            let target_commits = match &file.scope {
+                Some(dir) => git::get_commits_since_tag(last_tag.as_deref(), Some(dir))?,
+                None => commits.clone(),
+            };
+            if target_commits.is_empty() {
+                log(&format!("No changes for {}, skipping", path), target);
+                continue;
+            }
 
-    let mut all_updated_files = updated_files.clone();
-    if config.changelog {
-        all_updated_files.push("CHANGELOG.md".to_string());
+            let (update, changes) = changelog::plan_changelog_entry(
+                &new_version,
+                &git::subjects(&target_commits),
+                &file.path,
+                &released,
+                &config,
+            )?;
+            match update {
+                changelog::EntryUpdate::Inserted => log(&format!("Updated {}", path), target),
+                changelog::EntryUpdate::Replaced => log(
+                    &format!("Replaced existing {} entry in {}", new_version, path),
+                    target,
+                ),
+                changelog::EntryUpdate::Skipped => log(
+                    &format!(
+                        "{} already has an entry for {}, leaving it unchanged",
+                        path, new_version
+                    ),
+                    target,
+                ),
+            }
+            all_updated_files.extend(stage_updates(plan, changes));
+        }
     }
 
     if !all_updated_files.is_empty() {
         git::commit_changes(
+            plan,
             &new_version.to_string(),
             &all_updated_files,
             &config.commit_prefix,
-        )?;
+            &config.signing,
+        );
     }
+    report.files_changed.extend(all_updated_files);
 
+    let mut movable_tags = Vec::new();
     if config.tag {
-        git::create_tag(
+        let message = match config.tag_style {
+            TagStyle::Annotated => Some(changelog::render_tag_message(
+                &new_version,
+                bump,
+                &git::subjects(&commits),
+                &released,
+                &config,
+            )?),
+            TagStyle::Lightweight => None,
+        };
+        let tag_name = git::create_tag(
+            plan,
             &new_version.to_string(),
             &config.tag_prefix,
-            release_notes_message.as_deref(),
-        )?;
+            message.as_deref(),
+            &config.signing,
+        );
+        report.tags_created.push(tag_name);
 
-        // Update major/minor version tags if requested
-        if config.update_major_tag || config.update_minor_tag {
-            git::update_movable_tags(
+        // Update major/minor version tags if requested. Prereleases leave
+        // them on the latest release.
+        if (config.update_major_tag || config.update_minor_tag) && !new_version.is_prerelease() {
+            let moved = git::update_movable_tags(
+                plan,
                 &new_version,
                 &config.tag_prefix,
                 config.update_major_tag,
                 config.update_minor_tag,
-            )?;
+                &existing_versions,
+            );
+            let requested = [config.update_major_tag, config.update_minor_tag]
+                .iter()
+                .filter(|update| **update)
+                .count();
+            if moved.len() < requested {
+                log(
+                    "Left movable tags on the newer releases in their line",
+                    target,
+                );
+            }
+            report.tags_created.extend(moved.iter().cloned());
+            movable_tags = moved;
         }
     }
 
     if config.push {
-        if config.update_major_tag || config.update_minor_tag {
-            git::push_with_force_tags()?;
+        // Without a branch pre-flight checks stop the release
+        let branch = config
+            .remote_branch
+            .as_deref()
+            .or(config.branch.branch.as_deref());
+        git::push(
+            plan,
+            &config.remote,
+            branch,
+            &report.tags_created,
+            &movable_tags,
+        );
+    }
+
+    Ok(())
+}
+
+fn source_date_epoch() -> Option<String> {
+    std::env::var("SOURCE_DATE_EPOCH").ok()
+}
+
+/// Add the package file writes and tool runs to the plan and return the
+/// paths to stage
+fn stage_package(
+    plan: &mut ReleasePlan,
+    strategy: &dyn Strategy,
+    version: &Version,
+) -> BumperResult<Vec<String>> {
+    let mut paths = stage_updates(plan, strategy.plan_updates(version)?);
+    for command in strategy.plan_commands()? {
+        paths.extend(command.files.iter().map(|(path, _)| path.clone()));
+        plan.tool(command);
+    }
+    Ok(paths)
+}
+
+/// Add file writes to the plan and return the paths to stage
+fn stage_updates(
+    plan: &mut ReleasePlan,
+    updates: impl IntoIterator<Item = FileUpdate>,
+) -> Vec<String> {
+    updates
+        .into_iter()
+        .map(|update| {
+            let path = update.path.clone();
+            plan.write(update);
+            path
+        })
+        .collect()
+}
+
+/// Print the changelog entry the next release would get, without touching
+/// files, git config or history. Status messages go to stderr so stdout only
+/// carries the entry.
+fn preview_changelog(
+    config: &Config,
+    format: PreviewFormat,
+    output: OutputFormat,
+) -> BumperResult<()> {
+    let pending = inspect(config)?;
+
+    if pending.bump == BumpType::None {
+        eprintln!("No version bump required, nothing to release.");
+        return Ok(());
+    }
+
+    let format = match (format, output) {
+        (_, OutputFormat::Json) | (PreviewFormat::Json, _) => changelog::Format::Json,
+        (PreviewFormat::Markdown, OutputFormat::Text) => changelog::Format::Markdown,
+    };
+    let released = changelog::release_timestamp(config, source_date_epoch().as_deref())?;
+    print!(
+        "{}",
+        changelog::render_preview(
+            &pending.next_version,
+            &git::subjects(&pending.commits),
+            format,
+            &released,
+            config
+        )?
+    );
+
+    Ok(())
+}
+
+/// Check everything a release depends on and report all problems at once
+fn verify(config: &Config, output: OutputFormat) -> BumperResult<()> {
+    let mut checks = VerifyReport::default();
+
+    if git::is_repository() {
+        checks.pass("Inside a git repository".to_string());
+    } else {
+        checks.fail("Not inside a git repository".to_string());
+    }
+
+    if git::is_shallow() {
+        match history::is_truncated(config) {
+            Ok(false) => checks.pass("Shallow clone, the last release tag is in reach".to_string()),
+            _ if config.shallow_clone == ShallowClone::Deepen => checks.warn(
+                "Shallow clone without a release tag in reach, history will be fetched".to_string(),
+            ),
+            _ => checks.fail(BumperError::ShallowClone.to_string()),
+        }
+    }
+
+    // Preset and types were validated when the config was loaded
+    checks.pass(format!("Config is valid (preset '{}')", config.preset));
+
+    for file in &config.package_files {
+        if std::path::Path::new(file).exists() {
+            checks.pass(format!("Package file {} exists", file));
         } else {
-            git::push()?;
+            checks.fail(format!("Package file {} not found", file));
         }
-        let mut actions = vec!["Pushed changes"];
-        if config.tag {
-            actions.push("and tags");
+    }
+
+    if config.signing.commits || config.signing.tags {
+        match git::check_signing(config) {
+            Ok(()) => checks.pass("Signing key available".to_string()),
+            Err(e) => checks.fail(e.to_string()),
         }
-        log(&format!("{}.", actions.join(" ")), is_raw);
-    } else {
-        // Only log if we effectively did something (commit or tag)
-        if !updated_files.is_empty() || config.tag {
-            let mut actions = vec!["Committed"];
-            if config.tag {
-                actions.push("and tagged");
+    }
+
+    let package_version = match load_strategy(config).get_current_version() {
+        Ok(version) => {
+            checks.pass(format!("Current version {}", version));
+            Some(version)
+        }
+        Err(e) => {
+            checks.fail(format!("Cannot read current version: {}", e));
+            None
+        }
+    };
+
+    match git::get_last_tag_version(config) {
+        Ok(Some(tag_version)) => {
+            if package_version.is_some_and(|v| v < tag_version) {
+                checks.warn(format!(
+                    "Package version is behind the latest tag {}{}, the next release will sync it",
+                    config.tag_prefix, tag_version
+                ));
+            } else {
+                checks.pass(format!("Latest tag {}{}", config.tag_prefix, tag_version));
+            }
+        }
+        Ok(None) => {
+            checks.warn("No release tags found, versioning starts from scratch".to_string())
+        }
+        Err(e) => checks.fail(format!("Cannot parse the latest tag: {}", e)),
+    }
+
+    if checks.problems == 0 {
+        if let Ok(pending) = inspect(config) {
+            checks.pass(format!(
+                "{} commit(s) since last release, next version {} ({})",
+                pending.commits.len(),
+                pending.next_version,
+                pending.bump.as_str()
+            ));
+            if !pending.analysis.unknown_commits.is_empty() {
+                checks.push(
+                    CheckStatus::Warn,
+                    format!(
+                        "{} commit(s) have unknown or unconfigured types:",
+                        pending.analysis.unknown_commits.len()
+                    ),
+                    pending.analysis.unknown_commits,
+                );
             }
-            log(&format!("{} locally.", actions.join(" ")), is_raw);
         }
     }
 
+    match output {
+        OutputFormat::Json => checks.print_json()?,
+        OutputFormat::Text => checks.print_text(),
+    }
+
+    if checks.problems > 0 {
+        return Err(BumperError::VerificationFailed(checks.problems));
+    }
     Ok(())
 }
 
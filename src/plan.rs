@@ -0,0 +1,472 @@
+use crate::error::{BumperError, BumperResult};
+use crate::git;
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// New contents for a file touched by a release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileUpdate {
+    pub path: String,
+    /// Current contents, `None` if the file will be created
+    pub original: Option<String>,
+    pub updated: String,
+}
+
+/// A tool other than git that updates files in place, such as
+/// `cargo update`. Like the tools it stands in for it is best effort, so a
+/// failure doesn't stop the release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Files the command may change, with their contents before it runs
+    pub files: Vec<(String, String)>,
+}
+
+/// `git <options> <args>`, kept apart so the subcommand is always `args[0]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitCommand {
+    /// Options before the subcommand, such as `-c gpg.format=ssh`
+    pub options: Vec<String>,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Write(FileUpdate),
+    Git(GitCommand),
+    Run(ToolCommand),
+}
+
+/// Every side effect of a release in the order it happens. The plan is built
+/// without touching anything, then either printed (`--dry-run`) or executed.
+#[derive(Debug, Default)]
+pub struct ReleasePlan {
+    pub actions: Vec<Action>,
+}
+
+impl ReleasePlan {
+    pub fn write(&mut self, update: FileUpdate) {
+        self.actions.push(Action::Write(update));
+    }
+
+    pub fn git(&mut self, args: &[&str]) {
+        self.git_with_options(&[], args);
+    }
+
+    /// Run git with `options` ahead of the subcommand that starts `args`
+    pub fn git_with_options(&mut self, options: &[String], args: &[&str]) {
+        self.actions.push(Action::Git(GitCommand {
+            options: options.to_vec(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }));
+    }
+
+    pub fn tool(&mut self, command: ToolCommand) {
+        self.actions.push(Action::Run(command));
+    }
+
+    /// Whether the plan runs the git subcommand `name`, e.g. `push`
+    pub fn runs_git(&self, name: &str) -> bool {
+        self.actions
+            .iter()
+            .any(|action| matches!(action, Action::Git(git) if git.args.first().is_some_and(|arg| arg == name)))
+    }
+
+    /// Stage `files` and commit them with the git `options` and arguments in
+    /// `commit`, if there are any files
+    pub fn commit(&mut self, files: &[String], options: &[String], commit: &[&str]) {
+        if files.is_empty() {
+            return;
+        }
+
+        let mut add_args = vec!["add"];
+        add_args.extend(files.iter().map(String::as_str));
+        self.git(&add_args);
+        self.git_with_options(options, commit);
+    }
+
+    /// Run every action in order. If one fails, the actions already run are
+    /// undone: files get their original contents back and HEAD and the local
+    /// tags are restored, so a failed push doesn't leave a half-made release
+    /// behind.
+    pub fn execute(&self) -> BumperResult<()> {
+        let snapshot = if self.actions.iter().any(|a| matches!(a, Action::Git(_))) {
+            Some(Snapshot::take()?)
+        } else {
+            None
+        };
+
+        for (index, action) in self.actions.iter().enumerate() {
+            if let Err(error) = run(action) {
+                let undone = &self.actions[..index];
+                return Err(match rollback(undone, snapshot.as_ref()) {
+                    Ok(()) => BumperError::RolledBack(Box::new(error)),
+                    Err(failure) => {
+                        BumperError::RollbackFailed(Box::new(error), failure.to_string())
+                    }
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Unified diffs of file changes and the git commands, as a dry run prints
+    /// them. A file written more than once is diffed against its previous
+    /// planned contents.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let mut planned: BTreeMap<&str, &str> = BTreeMap::new();
+
+        for action in &self.actions {
+            match action {
+                Action::Write(update) => {
+                    let before = planned
+                        .get(update.path.as_str())
+                        .copied()
+                        .or(update.original.as_deref());
+                    let original = before.unwrap_or("");
+                    let old_header = if before.is_some() {
+                        format!("a/{}", update.path)
+                    } else {
+                        "/dev/null".to_string()
+                    };
+                    let diff = TextDiff::from_lines(original, update.updated.as_str());
+                    out.push_str(
+                        &diff
+                            .unified_diff()
+                            .context_radius(2)
+                            .header(&old_header, &format!("b/{}", update.path))
+                            .to_string(),
+                    );
+                    planned.insert(&update.path, &update.updated);
+                }
+                Action::Git(command) => {
+                    let quoted: Vec<String> = command
+                        .options
+                        .iter()
+                        .chain(&command.args)
+                        .map(|a| shell_quote(a))
+                        .collect();
+                    out.push_str(&format!("$ git {}\n", quoted.join(" ")));
+                }
+                Action::Run(command) => {
+                    let quoted: Vec<String> = command.args.iter().map(|a| shell_quote(a)).collect();
+                    out.push_str(&format!("$ {} {}\n", command.program, quoted.join(" ")));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn run(action: &Action) -> BumperResult<()> {
+    match action {
+        Action::Write(update) => {
+            if let Some(parent) = Path::new(&update.path).parent() {
+                if !parent.as_os_str().is_empty() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+            fs::write(&update.path, &update.updated)?;
+        }
+        Action::Git(command) => {
+            let args: Vec<&str> = command
+                .options
+                .iter()
+                .chain(&command.args)
+                .map(String::as_str)
+                .collect();
+            git::run_git_command(&args)?;
+        }
+        Action::Run(command) => {
+            Command::new(&command.program)
+                .args(&command.args)
+                .output()
+                .ok();
+        }
+    }
+    Ok(())
+}
+
+/// HEAD and every local tag before a release runs
+struct Snapshot {
+    head: Option<String>,
+    tags: BTreeMap<String, String>,
+}
+
+impl Snapshot {
+    fn take() -> BumperResult<Self> {
+        Ok(Snapshot {
+            head: git::run_git_command(&["rev-parse", "-q", "--verify", "HEAD"]).ok(),
+            tags: git::tag_refs()?,
+        })
+    }
+}
+
+/// Undo `actions`, newest first: move tags back, drop release commits and
+/// restore the files that were written or updated by tools
+fn rollback(actions: &[Action], snapshot: Option<&Snapshot>) -> BumperResult<()> {
+    if let Some(snapshot) = snapshot {
+        let current = git::tag_refs()?;
+        for (name, target) in &current {
+            match snapshot.tags.get(name) {
+                Some(previous) if previous == target => {}
+                Some(previous) => {
+                    git::run_git_command(&["update-ref", name, previous])?;
+                }
+                None => {
+                    git::run_git_command(&["update-ref", "-d", name])?;
+                }
+            }
+        }
+        for (name, previous) in &snapshot.tags {
+            if !current.contains_key(name) {
+                git::run_git_command(&["update-ref", name, previous])?;
+            }
+        }
+        if let Some(head) = &snapshot.head {
+            git::run_git_command(&["reset", "-q", "--soft", head])?;
+        }
+    }
+
+    // Each changed path with its contents before, `None` if it was created
+    let written: Vec<(&str, Option<&str>)> = actions
+        .iter()
+        .flat_map(|action| match action {
+            Action::Write(update) => vec![(update.path.as_str(), update.original.as_deref())],
+            Action::Run(command) => command
+                .files
+                .iter()
+                .map(|(path, original)| (path.as_str(), Some(original.as_str())))
+                .collect(),
+            Action::Git(_) => Vec::new(),
+        })
+        .collect();
+    if let (Some(snapshot), false) = (snapshot, written.is_empty()) {
+        if let Some(head) = &snapshot.head {
+            let mut args = vec!["reset", "-q", head.as_str(), "--"];
+            args.extend(written.iter().map(|(path, _)| *path));
+            git::run_git_command(&args)?;
+        }
+    }
+    for (path, original) in written.iter().rev() {
+        match original {
+            Some(original) => fs::write(path, original)?,
+            None => fs::remove_file(path)?,
+        }
+    }
+    Ok(())
+}
+
+/// Quote an argument so a printed command can be pasted into a shell
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@+,".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_repo::TestRepo;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_commit_skips_empty_file_list() {
+        let mut plan = ReleasePlan::default();
+        plan.commit(&[], &[], &["commit", "-m", "chore: bump version to 1.0.0"]);
+
+        assert!(plan.actions.is_empty());
+    }
+
+    #[test]
+    fn test_describe_shows_diff_and_quoted_commands() {
+        let mut plan = ReleasePlan::default();
+        plan.write(FileUpdate {
+            path: "Cargo.toml".to_string(),
+            original: Some("name = \"demo\"\nversion = \"1.0.0\"\n".to_string()),
+            updated: "name = \"demo\"\nversion = \"1.1.0\"\n".to_string(),
+        });
+        plan.commit(
+            &["Cargo.toml".to_string()],
+            &[],
+            &["commit", "-m", "chore: bump version to 1.1.0"],
+        );
+
+        let described = plan.describe();
+
+        assert!(described.contains("--- a/Cargo.toml\n+++ b/Cargo.toml"));
+        assert!(described.contains("-version = \"1.0.0\"\n+version = \"1.1.0\""));
+        assert!(described.contains("$ git add Cargo.toml\n"));
+        assert!(described.contains("$ git commit -m 'chore: bump version to 1.1.0'\n"));
+    }
+
+    #[test]
+    fn test_describe_diffs_repeated_writes_against_the_plan() {
+        let mut plan = ReleasePlan::default();
+        for (original, updated) in [("1.0.0\n", "1.2.0\n"), ("1.0.0\n", "1.3.0\n")] {
+            plan.write(FileUpdate {
+                path: "VERSION".to_string(),
+                original: Some(original.to_string()),
+                updated: updated.to_string(),
+            });
+        }
+
+        let described = plan.describe();
+
+        assert!(described.contains("-1.0.0\n+1.2.0\n"));
+        assert!(described.contains("-1.2.0\n+1.3.0\n"));
+        assert!(!described.contains("-1.0.0\n+1.3.0\n"));
+    }
+
+    #[test]
+    fn test_execute_writes_files_and_creates_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("docs/CHANGELOG.md");
+        let mut plan = ReleasePlan::default();
+        plan.write(FileUpdate {
+            path: path.to_string_lossy().to_string(),
+            original: None,
+            updated: "# Changelog\n".to_string(),
+        });
+
+        plan.execute().unwrap();
+
+        assert_eq!(fs::read_to_string(path).unwrap(), "# Changelog\n");
+    }
+
+    #[test]
+    fn test_failed_execute_restores_written_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("VERSION");
+        let created = temp_dir.path().join("CHANGELOG.md");
+        fs::write(&existing, "1.0.0\n").unwrap();
+        let mut plan = ReleasePlan::default();
+        plan.write(FileUpdate {
+            path: existing.to_string_lossy().to_string(),
+            original: Some("1.0.0\n".to_string()),
+            updated: "1.1.0\n".to_string(),
+        });
+        plan.write(FileUpdate {
+            path: created.to_string_lossy().to_string(),
+            original: None,
+            updated: "# Changelog\n".to_string(),
+        });
+        // Fails because VERSION is a file, not a directory
+        plan.write(FileUpdate {
+            path: existing.join("nested").to_string_lossy().to_string(),
+            original: None,
+            updated: String::new(),
+        });
+
+        let result = plan.execute();
+
+        assert!(matches!(result, Err(BumperError::RolledBack(_))));
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "1.0.0\n");
+        assert!(!created.exists());
+    }
+
+    #[test]
+    fn test_failed_push_rolls_back_the_release() {
+        let repo = TestRepo::new();
+        repo.commit("VERSION", "1.0.0\n", "feat: start");
+        repo.git(&["tag", "v1.0.0"]);
+        repo.git(&["tag", "v1"]);
+        repo.git(&["remote", "add", "origin", "/nonexistent/remote.git"]);
+        let head = repo.git(&["rev-parse", "HEAD"]);
+        let tags = git::tag_refs().unwrap();
+
+        let path = repo.path("VERSION").to_string_lossy().to_string();
+        let mut plan = ReleasePlan::default();
+        plan.write(FileUpdate {
+            path: path.clone(),
+            original: Some("1.0.0\n".to_string()),
+            updated: "1.1.0\n".to_string(),
+        });
+        plan.commit(
+            std::slice::from_ref(&path),
+            &[],
+            &["commit", "-m", "chore: bump version to 1.1.0"],
+        );
+        plan.git(&["tag", "-a", "v1.1.0", "-m", "v1.1.0"]);
+        plan.git(&["tag", "-f", "v1"]);
+        plan.git(&[
+            "push",
+            "--atomic",
+            "origin",
+            "HEAD:refs/heads/main",
+            "refs/tags/v1.1.0",
+        ]);
+
+        let result = plan.execute();
+
+        // The push is what failed, after the commit and tags were made
+        let Err(BumperError::RolledBack(error)) = result else {
+            panic!("expected a rolled back release, got {:?}", result);
+        };
+        assert!(error.to_string().contains("/nonexistent/remote.git"));
+        assert_eq!(repo.git(&["rev-parse", "HEAD"]), head);
+        assert_eq!(git::tag_refs().unwrap(), tags);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1.0.0\n");
+        assert_eq!(repo.git(&["status", "--porcelain"]), "");
+    }
+
+    #[test]
+    fn test_tool_commands_are_described_and_undone() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock = temp_dir.path().join("Cargo.lock");
+        fs::write(&lock, "version = 3\n").unwrap();
+        let lock_path = lock.to_string_lossy().to_string();
+        let mut plan = ReleasePlan::default();
+        plan.tool(ToolCommand {
+            program: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                format!("echo version = 4 > {}", lock_path),
+            ],
+            files: vec![(lock_path.clone(), "version = 3\n".to_string())],
+        });
+        // Fails because Cargo.lock is a file, not a directory
+        plan.write(FileUpdate {
+            path: lock.join("nested").to_string_lossy().to_string(),
+            original: None,
+            updated: String::new(),
+        });
+
+        assert!(plan.describe().starts_with("$ sh -c 'echo version = 4 > "));
+        assert!(!plan.runs_git("commit"));
+
+        let result = plan.execute();
+
+        assert!(matches!(result, Err(BumperError::RolledBack(_))));
+        assert_eq!(fs::read_to_string(&lock).unwrap(), "version = 3\n");
+    }
+
+    #[test]
+    fn test_runs_git() {
+        let mut plan = ReleasePlan::default();
+        plan.git(&["tag", "v1.0.0"]);
+        plan.git(&["push", "origin", "refs/tags/v1.0.0"]);
+
+        assert!(plan.runs_git("push"));
+        assert!(!plan.runs_git("commit"));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("v1.2.3"), "v1.2.3");
+        assert_eq!(shell_quote("it's here"), r"'it'\''s here'");
+        assert_eq!(shell_quote(""), "''");
+    }
+}
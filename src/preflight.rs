@@ -0,0 +1,223 @@
+use crate::config::{Config, ShallowClone};
+use crate::error::{BumperError, BumperResult};
+use crate::git;
+use crate::history;
+use crate::plan::{Action, ReleasePlan};
+use crate::report::Report;
+
+/// Everything that would make a planned release fail or go wrong, found
+/// before anything is changed. Empty when the release can go ahead.
+pub fn check(config: &Config, report: &Report, plan: &ReleasePlan) -> BumperResult<Vec<String>> {
+    let mut problems = Vec::new();
+
+    let changed = git::changed_files()?;
+    if !changed.is_empty() {
+        problems.push(format!(
+            "Uncommitted changes to {}; commit or stash them first",
+            changed.join(", ")
+        ));
+    }
+
+    // Files that exist now and are changed by the release
+    let existing = plan.actions.iter().flat_map(|action| match action {
+        Action::Write(update) if update.original.is_some() => vec![update.path.as_str()],
+        Action::Run(command) => command
+            .files
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect(),
+        _ => Vec::new(),
+    });
+    for path in existing {
+        if !git::is_tracked(path) {
+            problems.push(format!(
+                "{} is not tracked by git, so the release commit would miss it",
+                path
+            ));
+        }
+    }
+
+    // With `deepen` the history was fetched before planning
+    if config.shallow_clone == ShallowClone::Fail && history::is_truncated(config)? {
+        problems.push(BumperError::ShallowClone.to_string());
+    }
+
+    let tag = match (&report.new_version, config.tag) {
+        (Some(version), true) => Some(format!("{}{}", config.tag_prefix, version)),
+        _ => None,
+    };
+    if let Some(tag) = &tag {
+        if git::tag_refs()?.contains_key(&format!("refs/tags/{}", tag)) {
+            problems.push(format!("Tag {} already exists locally", tag));
+        }
+    }
+
+    if config.push {
+        check_remote(config, tag.as_deref(), &mut problems);
+    }
+
+    Ok(problems)
+}
+
+/// Problems only the remote can reveal: the branch being behind it, or the
+/// release tag already being there
+fn check_remote(config: &Config, tag: Option<&str>, problems: &mut Vec<String>) {
+    let remote = &config.remote;
+    let branch = config
+        .remote_branch
+        .as_deref()
+        .or(config.branch.branch.as_deref());
+    if branch.is_none() {
+        problems.push(
+            "HEAD is detached and no branch is known, so there is nowhere to push; \
+             check out a branch or set remoteBranch"
+                .to_string(),
+        );
+    }
+
+    let branch_ref = branch.map(|branch| format!("refs/heads/{}", branch));
+    let tag_ref = tag.map(|tag| format!("refs/tags/{}", tag));
+    let wanted: Vec<String> = branch_ref.iter().chain(&tag_ref).cloned().collect();
+    let found = match git::remote_refs(remote, &wanted) {
+        Ok(found) => found,
+        Err(e) => {
+            problems.push(format!(
+                "Cannot reach remote {}: {}",
+                remote,
+                e.to_string().trim()
+            ));
+            return;
+        }
+    };
+
+    if let (Some(branch), Some(target)) = (branch, branch_ref.and_then(|r| found.get(&r))) {
+        if !git::is_ancestor(target, "HEAD") {
+            problems.push(format!(
+                "Branch {} is behind or has diverged from {}/{}; pull before releasing",
+                branch, remote, branch
+            ));
+        }
+    }
+    if let (Some(tag), Some(tag_ref)) = (tag, tag_ref) {
+        if found.contains_key(&tag_ref) {
+            problems.push(format!("Tag {} already exists on {}", tag, remote));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_repo::TestRepo;
+    use crate::plan::FileUpdate;
+    use crate::versioner::Version;
+
+    /// A remote with one commit and a clone of it, used by git commands
+    fn cloned() -> (TestRepo, TestRepo) {
+        let origin = TestRepo::new();
+        origin.commit("notes.txt", "1", "feat: start");
+        let clone = TestRepo::clone_of(&origin, &[]);
+        (origin, clone)
+    }
+
+    fn config(push: bool) -> Config {
+        let mut config = Config {
+            push,
+            tag: true,
+            ..Config::default()
+        };
+        config.branch.branch = Some("main".to_string());
+        config
+    }
+
+    fn report() -> Report {
+        Report {
+            new_version: Some(Version::parse("1.1.0").unwrap()),
+            ..Report::default()
+        }
+    }
+
+    #[test]
+    fn test_clean_repository_has_no_problems() {
+        let (_origin, _clone) = cloned();
+
+        let problems = check(&config(true), &report(), &ReleasePlan::default()).unwrap();
+
+        assert_eq!(problems, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_dirty_tree_and_untracked_package_file() {
+        let (_origin, clone) = cloned();
+        clone.write("notes.txt", "changed");
+        clone.write("package.json", "{}");
+        let mut plan = ReleasePlan::default();
+        plan.write(FileUpdate {
+            path: "package.json".to_string(),
+            original: Some("{}".to_string()),
+            updated: "{\"version\": \"1.1.0\"}".to_string(),
+        });
+        // Created by the release, so it needn't be tracked yet
+        plan.write(FileUpdate {
+            path: "CHANGELOG.md".to_string(),
+            original: None,
+            updated: "# Changelog\n".to_string(),
+        });
+
+        let problems = check(&config(false), &report(), &plan).unwrap();
+
+        assert_eq!(
+            problems,
+            vec![
+                "Uncommitted changes to notes.txt; commit or stash them first",
+                "package.json is not tracked by git, so the release commit would miss it",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_collisions() {
+        let (origin, clone) = cloned();
+        clone.git(&["tag", "v1.1.0"]);
+        origin.git(&["tag", "v1.1.0"]);
+
+        let problems = check(&config(true), &report(), &ReleasePlan::default()).unwrap();
+
+        assert_eq!(
+            problems,
+            vec![
+                "Tag v1.1.0 already exists locally",
+                "Tag v1.1.0 already exists on origin",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_branch_behind_remote() {
+        let (origin, _clone) = cloned();
+        origin.commit("notes.txt", "2", "fix: later");
+
+        let problems = check(&config(true), &report(), &ReleasePlan::default()).unwrap();
+
+        assert_eq!(
+            problems,
+            vec!["Branch main is behind or has diverged from origin/main; pull before releasing"]
+        );
+    }
+
+    #[test]
+    fn test_detached_head_with_push() {
+        let (_origin, clone) = cloned();
+        clone.git(&["checkout", "-q", "--detach"]);
+        let mut config = config(true);
+        config.branch.branch = None;
+
+        let problems = check(&config, &report(), &ReleasePlan::default()).unwrap();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("HEAD is detached and no branch is known"));
+
+        config.remote_branch = Some("main".to_string());
+        let problems = check(&config, &report(), &ReleasePlan::default()).unwrap();
+        assert!(problems.is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use crate::analyser::{BumpType, CommitAnalysis};
+use crate::config::Origin;
+use crate::error::BumperResult;
+use crate::versioner::Version;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Machine-readable summary of a run, printed with `--output json`
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub previous_version: Option<Version>,
+    pub new_version: Option<Version>,
+    pub bump_type: BumpType,
+    pub last_tag: Option<String>,
+    /// Branch the release was computed for
+    pub branch: Option<String>,
+    pub commits: Vec<CommitAnalysis>,
+    pub triggering_commits: Vec<String>,
+    pub unknown_commits: Vec<String>,
+    pub files_changed: Vec<String>,
+    pub tags_created: Vec<String>,
+    pub committed: bool,
+    pub pushed: bool,
+    /// Set by `--dry-run`: the report describes what would happen
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One line of `grubble verify` output
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub status: CheckStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+/// Result of `grubble verify`, printed with `--output json`
+#[derive(Debug, Default, Serialize)]
+pub struct VerifyReport {
+    pub problems: usize,
+    pub checks: Vec<Check>,
+}
+
+impl VerifyReport {
+    pub fn pass(&mut self, message: String) {
+        self.push(CheckStatus::Pass, message, Vec::new());
+    }
+
+    pub fn warn(&mut self, message: String) {
+        self.push(CheckStatus::Warn, message, Vec::new());
+    }
+
+    pub fn fail(&mut self, message: String) {
+        self.push(CheckStatus::Fail, message, Vec::new());
+    }
+
+    pub fn push(&mut self, status: CheckStatus, message: String, details: Vec<String>) {
+        if status == CheckStatus::Fail {
+            self.problems += 1;
+        }
+        self.checks.push(Check {
+            status,
+            message,
+            details,
+        });
+    }
+
+    pub fn print_text(&self) {
+        for check in &self.checks {
+            let symbol = match check.status {
+                CheckStatus::Pass => "✓",
+                CheckStatus::Warn => "⚠",
+                CheckStatus::Fail => "✗",
+            };
+            println!("{} {}", symbol, check.message);
+            for detail in &check.details {
+                println!("    - {}", detail);
+            }
+        }
+    }
+
+    pub fn print_json(&self) -> BumperResult<()> {
+        println!("{}", serde_json::to_string_pretty(self)?);
+        Ok(())
+    }
+}
+
+impl Report {
+    pub fn print(&self) -> BumperResult<()> {
+        println!("{}", serde_json::to_string_pretty(self)?);
+        Ok(())
+    }
+}
+
+/// Render one row per commit showing how it was classified, for `grubble explain`
+pub fn explain_table(commits: &[CommitAnalysis]) -> String {
+    let header = [
+        "COMMIT", "TYPE", "SCOPE", "BREAKING", "RULE", "BUMP", "SUBJECT",
+    ];
+    let rows: Vec<[String; 7]> = commits
+        .iter()
+        .map(|c| {
+            let rule = match (&c.rule, &c.commit_type) {
+                (Some(rule), _) => rule.clone(),
+                (None, _) if c.skipped => "-".to_string(),
+                (None, Some(_)) if c.breaking => "breaking".to_string(),
+                (None, Some(_)) => "unknown type".to_string(),
+                (None, None) => "not conventional".to_string(),
+            };
+            let bump = if c.skipped {
+                "skipped (release commit)".to_string()
+            } else {
+                c.bump.as_str().to_string()
+            };
+            [
+                c.hash.clone(),
+                c.commit_type.clone().unwrap_or_else(|| "-".to_string()),
+                c.scope.clone().unwrap_or_else(|| "-".to_string()),
+                match c.breaking_source {
+                    Some(source) if !source.is_breaking() => {
+                        format!("{} (ignored)", source.as_str())
+                    }
+                    Some(source) => source.as_str().to_string(),
+                    None => "-".to_string(),
+                },
+                rule,
+                bump,
+                c.message.clone(),
+            ]
+        })
+        .collect();
+
+    table(&header, &rows)
+}
+
+/// Effective config values for `grubble config show`
+pub fn config_table(entries: &[(String, Value, Origin)]) -> String {
+    let rows: Vec<[String; 3]> = entries
+        .iter()
+        .map(|(key, value, origin)| [key.clone(), value.to_string(), origin.to_string()])
+        .collect();
+
+    table(&["KEY", "VALUE", "SOURCE"], &rows)
+}
+
+/// Left-aligned columns; the last one isn't padded
+fn table<const N: usize>(header: &[&str; N], rows: &[[String; N]]) -> String {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &[&str]| {
+        let mut line = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if i + 1 == cells.len() {
+                line.push_str(cell);
+            } else {
+                line.push_str(&format!("{:width$}  ", cell, width = widths[i]));
+            }
+        }
+        line.push('\n');
+        line
+    };
+
+    let mut out = format_row(header);
+    for row in rows {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        out.push_str(&format_row(&cells));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_serializes_camel_case_fields() {
+        let report = Report {
+            previous_version: Some(Version::parse("1.2.3").unwrap()),
+            new_version: Some(Version::parse("1.3.0").unwrap()),
+            bump_type: BumpType::Minor,
+            last_tag: Some("v1.2.3".to_string()),
+            tags_created: vec!["v1.3.0".to_string()],
+            ..Report::default()
+        };
+
+        let value = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(value["previousVersion"], "1.2.3");
+        assert_eq!(value["newVersion"], "1.3.0");
+        assert_eq!(value["bumpType"], "minor");
+        assert_eq!(value["lastTag"], "v1.2.3");
+        assert_eq!(value["tagsCreated"][0], "v1.3.0");
+        assert_eq!(value["pushed"], false);
+    }
+
+    #[test]
+    fn test_explain_table_aligns_columns() {
+        let commits = vec![
+            CommitAnalysis {
+                hash: "abc1234".to_string(),
+                message: "feat(api)!: drop v1".to_string(),
+                commit_type: Some("feat".to_string()),
+                scope: Some("api".to_string()),
+                breaking: true,
+                breaking_source: Some(crate::analyser::BreakingSource::Bang),
+                rule: None,
+                bump: BumpType::Major,
+                known: true,
+                skipped: false,
+            },
+            CommitAnalysis {
+                hash: "def5678".to_string(),
+                message: "chore: bump version to 1.0.0".to_string(),
+                commit_type: None,
+                scope: None,
+                breaking: false,
+                breaking_source: None,
+                rule: None,
+                bump: BumpType::None,
+                known: false,
+                skipped: true,
+            },
+        ];
+
+        let table = explain_table(&commits);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("COMMIT   TYPE  SCOPE  BREAKING"));
+        assert!(lines[1].starts_with("abc1234  feat  api    bang      breaking"));
+        assert!(lines[1].ends_with("major                     feat(api)!: drop v1"));
+        assert!(lines[2].contains("skipped (release commit)"));
+    }
+
+    #[test]
+    fn test_verify_report_counts_failures() {
+        let mut report = VerifyReport::default();
+        report.pass("ok".to_string());
+        report.warn("hmm".to_string());
+        report.fail("broken".to_string());
+
+        let value = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(report.problems, 1);
+        assert_eq!(value["checks"][2]["status"], "fail");
+        assert!(value["checks"][0].get("details").is_none());
+    }
+}
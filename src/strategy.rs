@@ -1,10 +1,16 @@
 use crate::config::Config;
 use crate::error::BumperResult;
+use crate::plan::{FileUpdate, ToolCommand};
 use crate::versioner::Version;
 
 pub trait Strategy {
     fn get_current_version(&self) -> BumperResult<Version>;
-    fn update_files(&self, new_version: &Version) -> BumperResult<Vec<String>>;
+    /// Compute the new contents of the package files without writing them
+    fn plan_updates(&self, new_version: &Version) -> BumperResult<Vec<FileUpdate>>;
+    /// Tools to run once the package files are written, e.g. to refresh a lockfile
+    fn plan_commands(&self) -> BumperResult<Vec<ToolCommand>> {
+        Ok(Vec::new())
+    }
 }
 
 pub mod git;
@@ -12,10 +18,6 @@ pub mod node;
 pub mod rust;
 
 pub fn load_strategy(config: &Config) -> Box<dyn Strategy> {
-    if config.raw {
-        return Box::new(git::GitStrategy::new(config.clone()));
-    }
-
     match config.preset.as_str() {
         "node" => Box::new(node::NodeStrategy::new(config.clone())),
         "git" => Box::new(git::GitStrategy::new(config.clone())),
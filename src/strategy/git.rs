@@ -1,6 +1,7 @@
 use crate::config::Config;
 use crate::error::BumperResult;
-use crate::git::get_last_tag;
+use crate::git::get_last_tag_version;
+use crate::plan::FileUpdate;
 use crate::strategy::Strategy;
 use crate::versioner::Version;
 
@@ -16,27 +17,10 @@ impl GitStrategy {
 
 impl Strategy for GitStrategy {
     fn get_current_version(&self) -> BumperResult<Version> {
-        let last_tag = get_last_tag()?;
-
-        if let Some(tag) = last_tag {
-            let prefix = &self.config.tag_prefix;
-            let version_str = if tag.starts_with(prefix) {
-                &tag[prefix.len()..]
-            } else {
-                &tag
-            };
-
-            Version::parse(version_str)
-        } else {
-            Ok(Version {
-                major: 0,
-                minor: 0,
-                patch: 0,
-            })
-        }
+        Ok(get_last_tag_version(&self.config)?.unwrap_or_else(|| Version::new(0, 0, 0)))
     }
 
-    fn update_files(&self, _new_version: &Version) -> BumperResult<Vec<String>> {
+    fn plan_updates(&self, _new_version: &Version) -> BumperResult<Vec<FileUpdate>> {
         // Git strategy doesn't update any files
         Ok(vec![])
     }
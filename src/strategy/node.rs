@@ -1,5 +1,6 @@
 use crate::config::Config;
 use crate::error::{BumperError, BumperResult};
+use crate::plan::FileUpdate;
 use crate::strategy::Strategy;
 use crate::versioner::Version;
 use serde_json::Value;
@@ -33,8 +34,8 @@ impl Strategy for NodeStrategy {
         Version::parse(version_str)
     }
 
-    fn update_files(&self, new_version: &Version) -> BumperResult<Vec<String>> {
-        let mut updated = Vec::new();
+    fn plan_updates(&self, new_version: &Version) -> BumperResult<Vec<FileUpdate>> {
+        let mut updates = Vec::new();
 
         for file in &self.config.package_files {
             if std::path::Path::new(file).exists() {
@@ -44,12 +45,14 @@ impl Strategy for NodeStrategy {
                 package["version"] = Value::String(new_version.to_string());
 
                 let updated_content = serde_json::to_string_pretty(&package)?;
-                fs::write(file, format!("{}\n", updated_content))?;
-
-                updated.push(file.clone());
+                updates.push(FileUpdate {
+                    path: file.clone(),
+                    original: Some(content),
+                    updated: format!("{}\n", updated_content),
+                });
             }
         }
 
-        Ok(updated)
+        Ok(updates)
     }
 }
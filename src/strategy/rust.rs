@@ -1,5 +1,6 @@
 use crate::config::Config;
 use crate::error::{BumperError, BumperResult};
+use crate::plan::{FileUpdate, ToolCommand};
 use crate::strategy::Strategy;
 use crate::versioner::Version;
 use regex::Regex;
@@ -45,8 +46,8 @@ impl Strategy for RustStrategy {
         }
     }
 
-    fn update_files(&self, new_version: &Version) -> BumperResult<Vec<String>> {
-        let mut updated = Vec::new();
+    fn plan_updates(&self, new_version: &Version) -> BumperResult<Vec<FileUpdate>> {
+        let mut updates = Vec::new();
         let version_regex = Regex::new(r#"(?m)^version\s*=\s*"[^"]+""#).unwrap();
 
         for file in &self.config.package_files {
@@ -56,21 +57,27 @@ impl Strategy for RustStrategy {
                 let new_content =
                     version_regex.replace(&content, format!(r#"version = "{}""#, new_version));
 
-                fs::write(file, new_content.as_ref())?;
-                updated.push(file.clone());
+                updates.push(FileUpdate {
+                    path: file.clone(),
+                    updated: new_content.into_owned(),
+                    original: Some(content),
+                });
             }
         }
 
-        // Update Cargo.lock if it exists (for binary crates)
-        if std::path::Path::new("Cargo.lock").exists() {
-            // Run cargo update to refresh Cargo.lock with new version
-            std::process::Command::new("cargo")
-                .args(["update", "--workspace"])
-                .output()
-                .ok(); // Ignore errors, best effort
-            updated.push("Cargo.lock".to_string());
-        }
+        Ok(updates)
+    }
 
-        Ok(updated)
+    fn plan_commands(&self) -> BumperResult<Vec<ToolCommand>> {
+        // Refresh Cargo.lock with the new version if it exists (for binary crates)
+        let lock_file = "Cargo.lock";
+        if !std::path::Path::new(lock_file).exists() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![ToolCommand {
+            program: "cargo".to_string(),
+            args: vec!["update".to_string(), "--workspace".to_string()],
+            files: vec![(lock_file.to_string(), fs::read_to_string(lock_file)?)],
+        }])
     }
 }
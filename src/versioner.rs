@@ -1,67 +1,309 @@
 use crate::analyser::BumpType;
+use crate::config::InitialDevelopmentConfig;
 use crate::error::{BumperError, BumperResult};
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Version {
     pub major: u32,
     pub minor: u32,
     pub patch: u32,
+    /// Prerelease identifiers, e.g. `["next", 2]` for `1.0.0-next.2`
+    pub pre: Vec<Identifier>,
+}
+
+/// A dot-separated prerelease identifier. Numeric identifiers sort before
+/// alphanumeric ones, as SemVer requires.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Identifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identifier::Numeric(n) => write!(f, "{}", n),
+            Identifier::Alphanumeric(s) => write!(f, "{}", s),
+        }
+    }
 }
 
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            let pre: Vec<String> = self.pre.iter().map(Identifier::to_string).collect();
+            write!(f, "-{}", pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// SemVer precedence: a prerelease sorts before its release
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
 impl Version {
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+        }
+    }
+
+    /// Parse `X.Y.Z` with an optional `-prerelease`; `+build` metadata is ignored
     pub fn parse(version_str: &str) -> BumperResult<Self> {
-        let parts: Vec<&str> = version_str.split('.').collect();
+        let invalid = || BumperError::InvalidVersion(version_str.to_string());
+        let without_build = version_str.split('+').next().unwrap_or_default();
+        let (core, pre) = match without_build.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (without_build, None),
+        };
+
+        let parts: Vec<&str> = core.split('.').collect();
 
         if parts.len() != 3 {
-            return Err(BumperError::InvalidVersion(version_str.to_string()));
+            return Err(invalid());
         }
 
-        let major = parts[0]
-            .parse::<u32>()
-            .map_err(|_| BumperError::InvalidVersion(version_str.to_string()))?;
-        let minor = parts[1]
-            .parse::<u32>()
-            .map_err(|_| BumperError::InvalidVersion(version_str.to_string()))?;
-        let patch = parts[2]
-            .parse::<u32>()
-            .map_err(|_| BumperError::InvalidVersion(version_str.to_string()))?;
-
-        Ok(Version {
-            major,
-            minor,
-            patch,
-        })
+        let major = parts[0].parse::<u32>().map_err(|_| invalid())?;
+        let minor = parts[1].parse::<u32>().map_err(|_| invalid())?;
+        let patch = parts[2].parse::<u32>().map_err(|_| invalid())?;
+
+        let mut version = Version::new(major, minor, patch);
+        if let Some(pre) = pre {
+            for part in pre.split('.') {
+                if part.is_empty() || !part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                    return Err(invalid());
+                }
+                version.pre.push(match part.parse::<u64>() {
+                    Ok(n) => Identifier::Numeric(n),
+                    Err(_) => Identifier::Alphanumeric(part.to_string()),
+                });
+            }
+        }
+        Ok(version)
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// The release this version is, or is a prerelease of
+    pub fn release(&self) -> Self {
+        Version::new(self.major, self.minor, self.patch)
     }
 
+    /// `<channel>.<number>` prerelease of this version's release, e.g. `2.0.0-next.1`
+    pub fn with_prerelease(&self, channel: &str, number: u64) -> Self {
+        Version {
+            pre: vec![
+                Identifier::Alphanumeric(channel.to_string()),
+                Identifier::Numeric(number),
+            ],
+            ..self.release()
+        }
+    }
+
+    /// The number of a `<channel>.<number>` prerelease on `channel`
+    pub fn prerelease_number(&self, channel: &str) -> Option<u64> {
+        match self.pre.as_slice() {
+            [Identifier::Alphanumeric(name), Identifier::Numeric(n)] if name == channel => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Lower a bump computed from commits according to the `initialDevelopment`
+    /// rules while the major version is 0
+    pub fn initial_development_bump(
+        &self,
+        bump_type: BumpType,
+        rules: &InitialDevelopmentConfig,
+    ) -> BumpType {
+        if self.major != 0 {
+            return bump_type;
+        }
+        match bump_type {
+            BumpType::Major if rules.breaking_is_minor => BumpType::Minor,
+            BumpType::Minor if rules.features_are_patch => BumpType::Patch,
+            other => other,
+        }
+    }
+
+    /// The next release after a `bump_type` change. A prerelease already
+    /// ahead of its previous release by that much is released as is, so
+    /// `2.0.0-next.3` bumps to `2.0.0` for any bump.
     pub fn bump(&self, bump_type: BumpType) -> Self {
+        let pre = self.is_prerelease();
         match bump_type {
-            BumpType::Major => Version {
-                major: self.major + 1,
-                minor: 0,
-                patch: 0,
-            },
-            BumpType::Minor => Version {
-                major: self.major,
-                minor: self.minor + 1,
-                patch: 0,
-            },
-            BumpType::Patch => Version {
-                major: self.major,
-                minor: self.minor,
-                patch: self.patch + 1,
-            },
+            BumpType::Major if pre && self.minor == 0 && self.patch == 0 => self.release(),
+            BumpType::Major => Version::new(self.major + 1, 0, 0),
+            BumpType::Minor if pre && self.patch == 0 => self.release(),
+            BumpType::Minor => Version::new(self.major, self.minor + 1, 0),
+            BumpType::Patch if pre => self.release(),
+            BumpType::Patch => Version::new(self.major, self.minor, self.patch + 1),
             BumpType::None => self.clone(),
         }
     }
 }
 
+/// Versions a release line may produce, written `1.x`, `1.2.x` or
+/// `>=2.0.0 <3.0.0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    /// Inclusive lower bound
+    pub min: Version,
+    /// Exclusive upper bound
+    pub max: Version,
+}
+
+impl FromStr for VersionRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let invalid = || {
+            format!(
+                "invalid range \"{}\" (expected e.g. \"1.x\", \"1.2.x\" or \">=2.0.0 <3.0.0\")",
+                s
+            )
+        };
+        let number = |part: &str| part.parse::<u32>().map_err(|_| invalid());
+
+        let bounds: Vec<&str> = s.split_whitespace().collect();
+        if let [min, max] = bounds.as_slice() {
+            let bound = |text: Option<&str>| {
+                text.and_then(|v| Version::parse(v).ok())
+                    .ok_or_else(invalid)
+            };
+            let range = VersionRange {
+                min: bound(min.strip_prefix(">="))?,
+                max: bound(max.strip_prefix('<'))?,
+            };
+            if range.min >= range.max {
+                return Err(invalid());
+            }
+            return Ok(range);
+        }
+
+        let parts: Vec<&str> = s.trim().split('.').collect();
+        match parts.as_slice() {
+            [major, "x"] => {
+                let major = number(major)?;
+                Ok(VersionRange {
+                    min: Version::new(major, 0, 0),
+                    max: Version::new(major + 1, 0, 0),
+                })
+            }
+            [major, minor, "x"] => {
+                let (major, minor) = (number(major)?, number(minor)?);
+                Ok(VersionRange {
+                    min: Version::new(major, minor, 0),
+                    max: Version::new(major, minor + 1, 0),
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ">={} <{}", self.min, self.max)
+    }
+}
+
+impl VersionRange {
+    /// Whether `version`'s release falls in the range
+    pub fn contains(&self, version: &Version) -> bool {
+        let release = version.release();
+        self.min <= release && release < self.max
+    }
+}
+
+/// Target of `--release-as`: a bump level or an exact version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReleaseAs {
+    Bump(BumpType),
+    Version(Version),
+}
+
+impl FromStr for ReleaseAs {
+    type Err = BumperError;
+
+    fn from_str(s: &str) -> BumperResult<Self> {
+        match s {
+            "major" => Ok(ReleaseAs::Bump(BumpType::Major)),
+            "minor" => Ok(ReleaseAs::Bump(BumpType::Minor)),
+            "patch" => Ok(ReleaseAs::Bump(BumpType::Patch)),
+            _ => Ok(ReleaseAs::Version(Version::parse(
+                s.strip_prefix('v').unwrap_or(s),
+            )?)),
+        }
+    }
+}
+
+impl ReleaseAs {
+    /// The release version and the bump it amounts to, starting from
+    /// `current`. A bump level is a floor for the `computed` bump, while an
+    /// exact version replaces it.
+    pub fn resolve(
+        &self,
+        current: &Version,
+        computed: BumpType,
+    ) -> BumperResult<(Version, BumpType)> {
+        match self {
+            ReleaseAs::Bump(floor) => {
+                let bump = computed.max(*floor);
+                Ok((current.bump(bump), bump))
+            }
+            ReleaseAs::Version(version) => {
+                if version <= current {
+                    return Err(BumperError::VersionNotGreater(
+                        version.to_string(),
+                        current.to_string(),
+                    ));
+                }
+                let bump = if version.major != current.major {
+                    BumpType::Major
+                } else if version.minor != current.minor {
+                    BumpType::Minor
+                } else {
+                    BumpType::Patch
+                };
+                Ok((version.clone(), bump))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,4 +336,149 @@ mod tests {
         let bumped = version.bump(BumpType::Patch);
         assert_eq!(bumped.to_string(), "1.2.4");
     }
+
+    #[test]
+    fn test_prerelease_parse_and_precedence() {
+        let versions: Vec<Version> = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+        ]
+        .iter()
+        .map(|v| Version::parse(v).unwrap())
+        .collect();
+
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(versions[4].to_string(), "1.0.0-beta.11");
+        assert_eq!(
+            Version::parse("1.0.0+build.5").unwrap(),
+            Version::new(1, 0, 0)
+        );
+        assert!(Version::parse("1.0.0-").is_err());
+        assert!(Version::parse("1.0.0-a..b").is_err());
+    }
+
+    #[test]
+    fn test_prerelease_bump_and_channel() {
+        let next = Version::parse("2.0.0-next.3").unwrap();
+
+        assert_eq!(next.bump(BumpType::Minor).to_string(), "2.0.0");
+        assert_eq!(next.prerelease_number("next"), Some(3));
+        assert_eq!(next.prerelease_number("beta"), None);
+        assert_eq!(
+            Version::parse("1.2.1-next.1")
+                .unwrap()
+                .bump(BumpType::Minor)
+                .to_string(),
+            "1.3.0"
+        );
+        assert_eq!(
+            Version::new(1, 4, 0).with_prerelease("next", 1).to_string(),
+            "1.4.0-next.1"
+        );
+    }
+
+    #[test]
+    fn test_version_range() {
+        let range: VersionRange = "1.x".parse().unwrap();
+        let minor_range: VersionRange = "1.2.x".parse().unwrap();
+
+        assert!(range.contains(&Version::new(1, 9, 3)));
+        assert!(!range.contains(&Version::new(2, 0, 0)));
+        assert!(minor_range.contains(&Version::parse("1.2.5-next.1").unwrap()));
+        assert!(!minor_range.contains(&Version::new(1, 3, 0)));
+        assert_eq!(range.to_string(), ">=1.0.0 <2.0.0");
+        assert!("1.*".parse::<VersionRange>().is_err());
+
+        let bounded: VersionRange = ">=2.0.0 <3.0.0".parse().unwrap();
+        assert_eq!(bounded, "2.x".parse().unwrap());
+        assert!(">=3.0.0 <2.0.0".parse::<VersionRange>().is_err());
+        assert!("2.0.0 3.0.0".parse::<VersionRange>().is_err());
+    }
+
+    #[test]
+    fn test_release_as_parse() {
+        assert_eq!(
+            "minor".parse::<ReleaseAs>().unwrap(),
+            ReleaseAs::Bump(BumpType::Minor)
+        );
+        assert_eq!(
+            "v3.0.0".parse::<ReleaseAs>().unwrap(),
+            ReleaseAs::Version(Version::parse("3.0.0").unwrap())
+        );
+        assert!("huge".parse::<ReleaseAs>().is_err());
+    }
+
+    #[test]
+    fn test_release_as_explicit_version() {
+        let current = Version::parse("0.9.3").unwrap();
+        let release_as = ReleaseAs::Version(Version::parse("1.0.0").unwrap());
+
+        let (version, bump) = release_as.resolve(&current, BumpType::Patch).unwrap();
+
+        assert_eq!(version.to_string(), "1.0.0");
+        assert_eq!(bump, BumpType::Major);
+    }
+
+    #[test]
+    fn test_release_as_bump_is_a_floor() {
+        let current = Version::parse("0.1.0").unwrap();
+        let release_as = ReleaseAs::Bump(BumpType::Minor);
+
+        let (version, bump) = release_as.resolve(&current, BumpType::Patch).unwrap();
+        assert_eq!(version.to_string(), "0.2.0");
+        assert_eq!(bump, BumpType::Minor);
+
+        let (version, bump) = release_as.resolve(&current, BumpType::Major).unwrap();
+        assert_eq!(version.to_string(), "1.0.0");
+        assert_eq!(bump, BumpType::Major);
+
+        let (version, _) = release_as.resolve(&current, BumpType::None).unwrap();
+        assert_eq!(version.to_string(), "0.2.0");
+    }
+
+    #[test]
+    fn test_release_as_rejects_older_version() {
+        let current = Version::parse("2.1.0").unwrap();
+
+        for requested in ["2.1.0", "2.0.9"] {
+            let release_as = ReleaseAs::Version(Version::parse(requested).unwrap());
+            assert!(matches!(
+                release_as.resolve(&current, BumpType::Minor),
+                Err(BumperError::VersionNotGreater(_, _))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_initial_development_bump() {
+        let rules = InitialDevelopmentConfig {
+            breaking_is_minor: true,
+            features_are_patch: true,
+        };
+        let pre_release = Version::parse("0.4.2").unwrap();
+        let stable = Version::parse("1.4.2").unwrap();
+
+        assert_eq!(
+            pre_release.initial_development_bump(BumpType::Major, &rules),
+            BumpType::Minor
+        );
+        assert_eq!(
+            pre_release.initial_development_bump(BumpType::Minor, &rules),
+            BumpType::Patch
+        );
+        assert_eq!(
+            stable.initial_development_bump(BumpType::Major, &rules),
+            BumpType::Major
+        );
+        assert_eq!(
+            pre_release
+                .initial_development_bump(BumpType::Major, &InitialDevelopmentConfig::default()),
+            BumpType::Major
+        );
+    }
 }